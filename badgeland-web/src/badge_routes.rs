@@ -1,11 +1,10 @@
-use super::utils::{error::BadgeError, BadgeOptions, QueryInfo};
+use super::utils::{error::BadgeError, query_spec, render_spec, QueryInfo};
 use actix_web::{http, middleware, web, HttpRequest, HttpResponse};
 use awc::Client;
-use badgeland::{Badge, BadgeData, Icon, Size, Style};
+use badgeland::{BadgeContent, BadgeSpec, SegmentSpec, Size, SpecError};
 use serde::Deserialize;
 use std::{
     collections::hash_map::DefaultHasher,
-    convert::TryFrom,
     hash::{Hash, Hasher},
     str::from_utf8,
     time::Duration,
@@ -18,7 +17,8 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         web::scope("/b/")
             .wrap(middleware::DefaultHeaders::new().header("Cache-Control", format!("public, max-age={}", MAX_AGE)))
             .route("/{text}/", web::get().to(badge_handler))
-            .route("/{subject}/{text}/", web::get().to(badge_handler)),
+            .route("/{subject}/{text}/", web::get().to(badge_handler))
+            .route("/{subject}/{segments:.+}/", web::get().to(segments_handler)),
     );
 }
 
@@ -26,6 +26,7 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 async fn url_badge_handler(req: HttpRequest, query: web::Query<QueryInfo>) -> Result<HttpResponse, BadgeError> {
     let query: QueryInfo = query.into_inner();
     let url = query.source;
+    let logo = query.logo;
 
     let url = url
         .ok_or("source query param missing".to_string())
@@ -53,44 +54,16 @@ async fn url_badge_handler(req: HttpRequest, query: web::Query<QueryInfo>) -> Re
         })
         .collect::<Vec<_>>();
 
-    let data: BadgeOptions = resp.json().await?;
+    let data: BadgeSpec = resp.json().await?;
 
-    let mut badge = Badge::new();
-    badge.subject(&data.subject);
-
-    match (data.color, query.color) {
-        (_, Some(c)) | (Some(c), _) => {
-            badge.color(c);
-        }
-        _ => {}
-    }
-
-    let icon = match (&data.icon, &query.icon) {
-        (_, Some(i)) | (Some(i), _) => Icon::try_from(i.as_str()).ok(),
-        _ => None,
-    };
-
-    if let Some(i) = icon {
-        badge.icon(i);
-    }
-
-    let size = match (data.size, query.size) {
-        (_, Some(s)) | (Some(s), _) => s,
-        _ => Size::Medium,
-    };
-    badge.size(size);
-
-    let style = match (data.style, query.style) {
-        (_, Some(s)) | (Some(s), _) => s,
-        _ => Style::Classic,
+    // Query params take precedence over the source options
+    let spec = query_spec(&req)?.or(data);
+    let spec = BadgeSpec {
+        size: spec.size.or(Some(Size::Medium)),
+        ..spec
     };
-    badge.style(style);
 
-    let badge_svg = match (data.data, &data.text) {
-        (Some(d), _) => badge.data(&d.0).to_string(),
-        (_, Some(t)) => badge.text(t).to_string(),
-        _ => badge.to_string(),
-    };
+    let badge_svg = render_spec(&req, &spec, logo.as_deref())?;
 
     let mut resp = HttpResponse::Ok();
 
@@ -120,46 +93,75 @@ struct BadgeInfo {
     subject: Option<String>,
 }
 
-fn badge_handler((params, query): (web::Path<BadgeInfo>, web::Query<QueryInfo>)) -> HttpResponse {
-    let query = query.into_inner();
-    let mut req_badge = Badge::new();
-    if let Some(c) = &params.subject {
-        req_badge.subject(c);
+async fn badge_handler(
+    req: HttpRequest,
+    params: web::Path<BadgeInfo>,
+    query: web::Query<QueryInfo>,
+) -> Result<HttpResponse, BadgeError> {
+    let params = params.into_inner();
+    let query_spec = query_spec(&req)?;
+
+    let spec = match query_spec.content {
+        // The path only holds the subject, e.g. `/b/coverage/?progress=87.5`
+        BadgeContent::Progress(_) => BadgeSpec {
+            subject: params.subject.or(Some(params.text)),
+            ..BadgeSpec::default()
+        },
+        _ => BadgeSpec {
+            subject: params.subject,
+            content: params.text.parse().unwrap_or_default(),
+            ..BadgeSpec::default()
+        },
     }
+    .or(query_spec);
 
-    if let Some(c) = query.color {
-        req_badge.color(c);
-    }
+    badge_response(&req, &spec, query.logo.as_deref())
+}
 
-    if let Some(s) = query.style {
-        req_badge.style(s);
-    }
+#[derive(Deserialize)]
+struct SegmentsInfo {
+    subject: String,
+    segments: String,
+}
 
-    if let Some(i) = &query.icon {
-        let icon = Icon::try_from(i.as_str());
-        if let Ok(i) = icon {
-            req_badge.icon(i);
-        }
-        if let Some(ic) = query.icon_color {
-            req_badge.icon_color(ic);
-        }
-    }
+/// `/b/{subject}/{segment}/{segment}/...`, each path part a `text[;color=..][;icon=..]` segment
+async fn segments_handler(
+    req: HttpRequest,
+    params: web::Path<SegmentsInfo>,
+    query: web::Query<QueryInfo>,
+) -> Result<HttpResponse, BadgeError> {
+    let params = params.into_inner();
+
+    let segments = params
+        .segments
+        .split('/')
+        .map(str::parse)
+        .collect::<Result<Vec<SegmentSpec>, SpecError>>()
+        .map_err(|e| BadgeError::Http {
+            status: http::StatusCode::BAD_REQUEST,
+            description: e.to_string(),
+            url: Some(req.uri().to_string()),
+        })?;
 
-    if let Some(bs) = query.size {
-        req_badge.size(bs);
+    let spec = BadgeSpec {
+        subject: Some(params.subject),
+        content: BadgeContent::Segments(segments),
+        ..BadgeSpec::default()
     }
+    .or(query_spec(&req)?);
 
-    let badge_svg = match params.text.parse::<BadgeData>() {
-        Ok(data) if data.0.len() > 1 => req_badge.data(&data.0).to_string(),
-        _ => req_badge.text(&params.text).to_string(),
-    };
+    badge_response(&req, &spec, query.logo.as_deref())
+}
+
+fn badge_response(req: &HttpRequest, spec: &BadgeSpec, logo: Option<&str>) -> Result<HttpResponse, BadgeError> {
+    let badge_svg = render_spec(req, spec, logo)?;
 
     let mut hasher = DefaultHasher::new();
     badge_svg.hash(&mut hasher);
     let hash = hasher.finish();
 
-    HttpResponse::Ok()
+    Ok(HttpResponse::Ok()
         .set_header(http::header::ETAG, format!("b:{:x}", hash))
         .content_type("image/svg+xml")
-        .body(badge_svg)
+        .body(badge_svg))
 }
@@ -0,0 +1,22 @@
+use actix_web::{web, HttpResponse};
+use badgeland::search_icons;
+use serde::Deserialize;
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+#[derive(Deserialize, Debug)]
+pub struct SearchQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(icon_search_handler);
+}
+
+#[get("/icons/")]
+async fn icon_search_handler(query: web::Query<SearchQuery>) -> HttpResponse {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    HttpResponse::Ok().json(search_icons(&query.q, limit))
+}
@@ -4,6 +4,7 @@
 extern crate actix_web;
 
 mod badge_routes;
+mod icon_routes;
 mod utils;
 
 use actix_files::Files;
@@ -46,6 +47,7 @@ async fn main() -> io::Result<()> {
             .default_service(web::route().to(default_404))
             .service(index)
             .configure(badge_routes::config)
+            .configure(icon_routes::config)
             .service(
                 web::scope("/")
                     .wrap(
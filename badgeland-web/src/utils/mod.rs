@@ -1,27 +1,48 @@
 pub mod error;
 
-use badgeland::{BadgeData, Color, Size, Style};
+use actix_web::{http::StatusCode, HttpRequest};
+use badgeland::{BadgeSpec, Icon};
+use error::BadgeError;
 use serde::Deserialize;
 use std::str;
 
+/// Largest accepted `logo` data uri in bytes
+pub const MAX_LOGO_LEN: usize = 16 * 1024;
+
 #[derive(Deserialize, Debug)]
 pub struct QueryInfo {
     pub source: Option<String>,
-    pub color: Option<Color>,
-    pub icon: Option<String>,
-    pub icon_color: Option<Color>,
-    pub style: Option<Style>,
-    pub size: Option<Size>,
+    /// Raster logo as a base64 `data:` uri, drawn in place of the icon
+    pub logo: Option<String>,
+}
+
+/// Badge options passed as query params. Unknown params such as `source` are ignored.
+pub fn query_spec(req: &HttpRequest) -> Result<BadgeSpec, BadgeError> {
+    req.query_string().parse().map_err(|e: badgeland::SpecError| BadgeError::Http {
+        status: StatusCode::BAD_REQUEST,
+        description: e.to_string(),
+        url: Some(req.uri().to_string()),
+    })
 }
 
-#[derive(Deserialize)]
-pub struct BadgeOptions {
-    pub text: Option<String>,
-    pub subject: String,
-    pub style: Option<Style>,
-    pub size: Option<Size>,
-    pub color: Option<Color>,
-    pub icon: Option<String>,
-    pub icon_color: Option<Color>,
-    pub data: Option<BadgeData>,
+/// Render `spec`, with the `logo` query param as its icon when given.
+pub fn render_spec(req: &HttpRequest, spec: &BadgeSpec, logo: Option<&str>) -> Result<String, BadgeError> {
+    let logo = match logo {
+        Some(logo) => logo,
+        None => return Ok(spec.render()),
+    };
+    let bad_request = |status, description: String| BadgeError::Http {
+        status,
+        description,
+        url: Some(req.uri().to_string()),
+    };
+    if logo.len() > MAX_LOGO_LEN {
+        let description = format!("logo is larger than {} bytes", MAX_LOGO_LEN);
+        return Err(bad_request(StatusCode::PAYLOAD_TOO_LARGE, description));
+    }
+    // An unencoded `+` in the query decodes to a space
+    let logo = logo.replace(' ', "+");
+    let icon = Icon::from_data_uri("logo", &logo)
+        .map_err(|e| bad_request(StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(spec.render_with_icon(icon))
 }
@@ -1,8 +1,22 @@
-use badgeland::{Badge, Color, Icon, Size, Style};
+use badgeland::{icon_data_size, icon_keys, Badge, Color, Icon, Size, Style};
 use divan::Bencher;
-use std::convert::TryFrom;
+use std::{convert::TryFrom, time::Instant};
 
 fn main() {
+    // The first lookup inflates the built-in icons, so time it before the benches
+    let start = Instant::now();
+    divan::black_box(Icon::try_from("code").unwrap());
+    let first_lookup = start.elapsed();
+
+    let (deflated, inflated) = icon_data_size();
+    let binary = std::env::current_exe()
+        .and_then(|exe| exe.metadata())
+        .map_or(0, |m| m.len());
+    println!(
+        "icons: {} bytes embedded, {} bytes inflated, first lookup {:?}, bench binary {} bytes",
+        deflated, inflated, first_lookup, binary
+    );
+
     divan::main();
 }
 
@@ -130,6 +144,22 @@ fn icon_solid() {
     divan::black_box(svg);
 }
 
+#[divan::bench(args = ["code", "fa:code", "si:github"])]
+fn icon_lookup(name: &str) {
+    let icon = Icon::try_from(divan::black_box(name)).unwrap();
+    divan::black_box(icon);
+}
+
+#[divan::bench]
+fn icon_lookup_all(bencher: Bencher) {
+    let names = icon_keys();
+    bencher.bench_local(|| {
+        for name in &names {
+            divan::black_box(Icon::try_from(*name).unwrap());
+        }
+    })
+}
+
 #[divan::bench]
 fn data() {
     let mut data = Badge::new();
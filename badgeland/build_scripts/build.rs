@@ -1,51 +1,181 @@
 use phf_codegen::Map;
 use scraper::{Html, Selector};
-use std::collections::HashSet;
-use std::fs::File;
+use std::collections::{hash_map::Entry, HashMap};
+use std::fs::{self, File};
 use std::{
     env,
     io::{BufWriter, Write},
     path::Path,
 };
 
-fn generate_icon_map() {
-    let mut map = Map::<&str>::new();
-    let mut seen: HashSet<String> = HashSet::new();
+/// An icon pack compiled in when its feature is enabled. Unprefixed ids
+/// resolve to the first pack that has them, in `PACKS` order.
+struct Pack {
+    feature: &'static str,
+    prefix: &'static str,
+    file: &'static str,
+    name: &'static str,
+    license: &'static str,
+}
+
+const PACKS: [Pack; 3] = [
+    Pack {
+        feature: "icons-fa-solid",
+        prefix: "fa",
+        file: "solid.svg",
+        name: "fa-solid",
+        license: "CC-BY-4.0",
+    },
+    Pack {
+        feature: "icons-fa-brands",
+        prefix: "fab",
+        file: "brands.svg",
+        name: "fa-brands",
+        license: "CC-BY-4.0",
+    },
+    Pack {
+        feature: "icons-simple",
+        prefix: "si",
+        file: "simple-icons.svg",
+        name: "simple-icons",
+        license: "CC0-1.0",
+    },
+];
+
+// Brand colors are stored as 6 digit hex, without `#`
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 6 && color.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Cargo exposes enabled features to build scripts as env vars
+fn feature_enabled(feature: &str) -> bool {
+    let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+    env::var_os(var).is_some()
+}
 
+fn generate_icon_map() {
     let selector = Selector::parse("symbol").unwrap();
+    let icons_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("build_scripts/icons");
+
+    // Symbols without their id, which is set from the name an icon is looked up by,
+    // and their `IconMeta`
+    let mut symbols: Vec<(String, String)> = Vec::new();
+    // Lookup name to symbol index and the pack it came from
+    let mut names: HashMap<String, (usize, &str)> = HashMap::new();
+    let mut collisions: Vec<String> = Vec::new();
 
-    let doc = Html::parse_fragment(include_str!("./icons/solid.svg"));
-    for el in doc.select(&selector) {
-        let id = el.value().attr("id").unwrap();
-        if !seen.insert(id.to_string()) {
+    for Pack {
+        feature,
+        prefix,
+        file,
+        name: pack,
+        license,
+    } in PACKS
+    {
+        if !feature_enabled(feature) {
             continue;
         }
-        let sym = el.html();
-        map.entry(id, format!(r##"r#"{}"#"##, sym));
-    }
+        let path = icons_dir.join(file);
+        let sprite = match fs::read_to_string(&path) {
+            Ok(sprite) => sprite,
+            Err(_) => {
+                println!(
+                    "cargo:warning=`{}` is enabled but {} is missing, see scripts/ to download it",
+                    feature,
+                    path.display()
+                );
+                continue;
+            }
+        };
 
-    let doc = Html::parse_fragment(include_str!("./icons/simple-icons.svg"));
-    for el in doc.select(&selector) {
-        let id = el.value().attr("id").unwrap();
-        if !seen.insert(id.to_string()) {
-            continue;
+        let doc = Html::parse_fragment(&sprite);
+        for el in doc.select(&selector) {
+            let id = el.value().attr("id").unwrap();
+            let view_box = el.value().attr("viewBox").unwrap_or_default();
+            // Set by scripts/download-simple-icons.ts
+            let title = el.value().attr("data-title").unwrap_or(id);
+            let color = el.value().attr("data-color").filter(|c| is_hex_color(c));
+            let index = symbols.len();
+            let mut used = false;
+
+            for name in [format!("{}:{}", prefix, id), id.to_string()] {
+                match names.entry(name) {
+                    Entry::Vacant(e) => {
+                        e.insert((index, feature));
+                        used = true;
+                    }
+                    Entry::Occupied(e) if e.get().1 != feature => {
+                        collisions.push(format!(
+                            "`{}` from `{}` is shadowed by `{}`",
+                            e.key(),
+                            feature,
+                            e.get().1
+                        ));
+                    }
+                    // Duplicate ids within a pack keep the first symbol
+                    Entry::Occupied(_) => {}
+                }
+            }
+            if used {
+                let symbol = format!(
+                    r#"<symbol viewBox="{}">{}</symbol>"#,
+                    view_box,
+                    el.inner_html().trim()
+                );
+                let meta = format!(
+                    "IconMeta {{ title: {:?}, color: {:?}, pack: {:?}, license: {:?} }}",
+                    title, color, pack, license
+                );
+                symbols.push((symbol, meta));
+            }
         }
-        let sym = el.html();
-        map.entry(id, format!(r##"r#"{}"#"##, sym));
     }
 
-    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("icons_map.rs");
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    // Full report in OUT_DIR, a summary as a build warning
+    let report = Path::new(&out_dir).join("icon_collisions.txt");
+    fs::write(&report, collisions.join("\n")).expect("Failed to write icon collisions");
+    if !collisions.is_empty() {
+        println!(
+            "cargo:warning={} icon id collisions, the first pack wins. See {}",
+            collisions.len(),
+            report.display()
+        );
+    }
+
+    // All symbols deflated as one blob, the map holds each symbol's range in it
+    let mut blob = String::new();
+    let entries: Vec<String> = symbols
+        .iter()
+        .map(|(symbol, meta)| {
+            let start = blob.len();
+            blob.push_str(symbol);
+            format!("({}, {}, {})", start, symbol.len(), meta)
+        })
+        .collect();
+    let deflated = miniz_oxide::deflate::compress_to_vec(blob.as_bytes(), 10);
+    fs::write(Path::new(&out_dir).join("icons.bin"), deflated).expect("Failed to write icons");
+
+    let mut map = Map::<&str>::new();
+    for (name, (index, _)) in &names {
+        map.entry(name, &entries[*index]);
+    }
 
+    let path = Path::new(&out_dir).join("icons_map.rs");
     let mut file = BufWriter::new(File::create(path).unwrap());
     writeln!(
         &mut file,
-        "const SYMBOLS: phf::Map<&'static str, &'static str> = {};",
+        r#"const SYMBOLS_DEFLATED: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/icons.bin"));
+const SYMBOLS: phf::Map<&'static str, (u32, u32, IconMeta)> = {};"#,
         map.build()
     )
     .expect("Failed to build icon map");
 }
 
 fn main() {
+    println!("cargo:rerun-if-changed=build_scripts/build.rs");
+    println!("cargo:rerun-if-changed=build_scripts/icons");
     if cfg!(feature = "static_icons") {
         generate_icon_map()
     }
@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+use unicode_bidi::{get_base_direction, Direction, ParagraphBidiInfo};
+
+/// Whether `s` reads right to left, going by its first strong character.
+#[inline]
+pub(super) fn is_rtl(s: &str) -> bool {
+    get_base_direction(s) == Direction::Rtl
+}
+
+/// `s` reordered for drawing glyphs left to right. Arabic letters are not
+/// joined, so text in joining scripts is drawn with isolated forms.
+pub(super) fn visual_order(s: &str) -> Cow<'_, str> {
+    let info = ParagraphBidiInfo::new(s, None);
+    if !info.has_rtl() {
+        return Cow::Borrowed(s);
+    }
+    info.reorder_line(0..s.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_rtl, visual_order};
+
+    #[test]
+    fn direction_from_first_strong_char() {
+        assert!(is_rtl("עובר"));
+        assert!(is_rtl("123 مرحبا build"));
+        assert!(!is_rtl("build עובר"));
+        assert!(!is_rtl("1.0.0"));
+    }
+
+    #[test]
+    fn rtl_runs_are_reversed() {
+        assert_eq!(visual_order("build"), "build");
+        assert_eq!(visual_order("build אבג"), "build גבא");
+        assert_eq!(visual_order("אבג build"), "build גבא");
+    }
+}
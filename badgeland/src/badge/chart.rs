@@ -0,0 +1,53 @@
+use crate::ChartError;
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde_de")]
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// How data badges draw their series.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde_de", derive(Serialize))]
+pub enum Chart {
+    /// Sparkline with a light area fill underneath
+    #[default]
+    Line,
+    /// Filled area without the line
+    Area,
+    /// One bar per value, growing up or down from zero
+    Bars,
+}
+
+impl fmt::Display for Chart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Chart::Line => "Line",
+            Chart::Area => "Area",
+            Chart::Bars => "Bars",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl<'de> Deserialize<'de> for Chart {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        Chart::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+impl FromStr for Chart {
+    type Err = ChartError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "line" | "l" => Ok(Chart::Line),
+            "area" | "a" => Ok(Chart::Area),
+            "bars" | "bar" | "b" => Ok(Chart::Bars),
+            _ => Err(Self::Err {}),
+        }
+    }
+}
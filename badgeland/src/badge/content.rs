@@ -1,118 +1,179 @@
-use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
-use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use super::font::FontSet;
 use std::fmt::Write;
-use std::sync::RwLock;
 use unicode_normalization::UnicodeNormalization;
 
-static FONT: Lazy<FontRef<'static>> = Lazy::new(|| {
-    let font_data: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/resx/Verdana.ttf"));
-    FontRef::try_from_slice(font_data).expect("Error constructing Font")
-});
-
-// Precomputed ASCII glyph widths at PxScale = 1.0.
-// For ASCII-only text, we can sum these widths and scale by the requested
-// height to avoid per-call glyph measurement.
-static ASCII_WIDTHS_1_0: Lazy<[f32; 128]> = Lazy::new(|| {
-    let scaled_font = FONT.as_scaled(PxScale::from(1.0));
-    let mut arr = [0.0f32; 128];
-    for b in 0u8..=127u8 {
-        let c = b as char;
-        let glyph = scaled_font.scaled_glyph(c);
-        // Use bounds width for parity with previous behavior
-        let gb = scaled_font.glyph_bounds(&glyph);
-        arr[b as usize] = gb.width();
-    }
-    arr
-});
-
-// Cache for non-ASCII glyph widths keyed by (codepoint, scaled-height-key)
-// to avoid repeated glyph bounds calculations for common unicode characters.
-// The height key quantizes the font size by 0.1 to a u32 to avoid f32 hash keys.
-static UNICODE_WIDTH_CACHE: Lazy<RwLock<HashMap<(u32, u32), f32>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
-
 pub(crate) trait TextWidth {
-    fn text_width(&self, height: f32) -> usize;
+    fn text_width(&self, font_size: f32, fonts: &FontSet) -> usize;
 }
 
-impl<'a> TextWidth for &'a str {
+impl TextWidth for &str {
     #[inline]
-    fn text_width(&self, height: f32) -> usize {
+    fn text_width(&self, font_size: f32, fonts: &FontSet) -> usize {
         let s = self.trim();
         if s.is_empty() {
             return 0;
         }
 
-        // Fast path for ASCII-only strings: use cached widths at scale 1.0,
-        // scale them by the requested height, and apply the 1.12 factor to
-        // preserve the previous sizing behavior.
-        if s.is_ascii() {
-            let sum_1_0: f32 = s.as_bytes().iter().map(|&b| ASCII_WIDTHS_1_0[b as usize]).sum();
-            return (sum_1_0 * height * 1.12).floor() as usize;
-        }
+        let width = if s.is_ascii() {
+            fonts.text_width(s, font_size)
+        } else {
+            fonts.text_width(&s.nfc().collect::<String>(), font_size)
+        };
+
+        width.ceil() as usize
+    }
+}
 
-        // General path without allocation: iterate NFC chars directly.
-        let scale = PxScale::from(height);
-        let scaled_font = FONT.as_scaled(scale);
+/// Optional bounds overriding the data min / max when scaling a chart.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub(super) struct ChartRange {
+    pub(super) min: Option<f32>,
+    pub(super) max: Option<f32>,
+}
 
-        // Quantize height to 0.1 precision for cache key
-        let height_key = (height * 10.0).round() as u32;
+// Maps values onto chart y coordinates. Values outside an overridden range
+// are clamped to the chart edges.
+struct YScale {
+    lo: f32,
+    hi: f32,
+    height: f32,
+    // y used for every value when the range is empty (constant series)
+    flat: f32,
+}
 
-        let mut total = 0.0f32;
-        for c in s.nfc() {
-            let key = (c as u32, height_key);
-            // Fast read-lock path
-            if let Some(&w) = UNICODE_WIDTH_CACHE.read().unwrap().get(&key) {
-                total += w * 1.12;
-                continue;
-            }
+impl YScale {
+    fn new(values: &[f32], range: ChartRange, height: usize, include_zero: bool) -> Self {
+        let init = if include_zero {
+            (0.0, 0.0)
+        } else {
+            (f32::INFINITY, f32::NEG_INFINITY)
+        };
+        let (lo, hi) = values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .fold(init, |(lo, hi), v| (lo.min(v), hi.max(v)));
+
+        let lo = range.min.filter(|v| v.is_finite()).unwrap_or(lo);
+        let hi = range.max.filter(|v| v.is_finite()).unwrap_or(hi);
+
+        let height = height as f32;
+        YScale {
+            lo,
+            hi,
+            height,
+            flat: if include_zero { height } else { height / 2.0 },
+        }
+    }
 
-            // Miss: measure without holding lock
-            let glyph = scaled_font.scaled_glyph(c);
-            let gb = scaled_font.glyph_bounds(&glyph);
-            let measured_w = gb.width();
-
-            // Insert with write-lock; prefer existing if inserted by another thread
-            let w = {
-                let mut map = UNICODE_WIDTH_CACHE.write().unwrap();
-                *map.entry(key).or_insert(measured_w)
-            };
-            total += w * 1.12;
+    #[inline]
+    fn y(&self, v: f32) -> f32 {
+        if self.hi > self.lo {
+            // f64 keeps the span finite for series near f32::MAX / f32::MIN
+            let t = (v as f64 - self.lo as f64) / (self.hi as f64 - self.lo as f64);
+            self.height - t.clamp(0.0, 1.0) as f32 * self.height
+        } else {
+            self.flat
         }
-        total.floor() as usize
     }
 }
 
 pub(super) trait SvgPath {
-    fn svg_path(&self, height: usize, width: usize) -> String;
+    /// Line through every finite value. Non-finite values break the line.
+    fn svg_path(&self, height: usize, width: usize, range: ChartRange) -> String;
+    /// Closed area under each run of `svg_path`.
+    fn svg_area(&self, height: usize, width: usize, range: ChartRange) -> String;
+    /// One bar per finite value, growing from zero.
+    fn svg_bars(&self, height: usize, width: usize, range: ChartRange) -> String;
 }
 
-impl<'a> SvgPath for [f32] {
-    fn svg_path(&self, height: usize, width: usize) -> String {
-        let len = self.len();
-        let chart_height = height as f32;
-        let max = self.iter().copied().fold(0.0_f32, f32::max);
-
-        let y_offset = chart_height / max;
-        let x_offset = width as f32 / (len as f32 - 1.0);
-
+impl SvgPath for [f32] {
+    fn svg_path(&self, height: usize, width: usize, range: ChartRange) -> String {
         // Reserve a larger buffer to reduce reallocations for big series
         // Each segment roughly contributes ~20-30 bytes (command + two floats)
-        let mut path_str = String::with_capacity(8 + len * 28);
+        let mut path_str = String::with_capacity(8 + self.len() * 28);
+
+        for run in line_runs(self, height, width, range) {
+            for (i, (x, y)) in run.iter().enumerate() {
+                let cmd = if i == 0 { 'M' } else { 'L' };
+                write!(&mut path_str, "{cmd}{x} {y}", cmd = cmd, x = x, y = y).unwrap()
+            }
+        }
+        path_str
+    }
+
+    fn svg_area(&self, height: usize, width: usize, range: ChartRange) -> String {
+        let mut path_str = String::with_capacity(8 + self.len() * 28);
 
-        for (i, v) in self.iter().enumerate() {
-            let x = i as f32 * x_offset;
-            let y = chart_height - y_offset * v;
-            if i == 0 {
-                write!(&mut path_str, "M0 {y}", y = y).unwrap();
+        for run in line_runs(self, height, width, range) {
+            let (first, last) = (run[0].0, run[run.len() - 1].0);
+            write!(&mut path_str, "M{x} {h}", x = first, h = height).unwrap();
+            for (x, y) in run.iter() {
+                write!(&mut path_str, "L{x} {y}", x = x, y = y).unwrap()
             }
-            write!(&mut path_str, "L{x} {y}", x = x, y = y).unwrap()
+            write!(&mut path_str, "L{x} {h}Z", x = last, h = height).unwrap();
+        }
+        path_str
+    }
+
+    fn svg_bars(&self, height: usize, width: usize, range: ChartRange) -> String {
+        let len = self.len();
+
+        // Bars grow from zero, so the range always includes it
+        let scale = YScale::new(self, range, height, true);
+        let zero = scale.y(0.0);
+
+        let slot = width as f32 / len as f32;
+        let gap = slot * 0.1;
+
+        let mut path_str = String::with_capacity(len * 40);
+
+        for (i, v) in self.iter().enumerate().filter(|(_, v)| v.is_finite()) {
+            let x = i as f32 * slot + gap / 2.0;
+            write!(
+                &mut path_str,
+                "M{x} {zero}V{y}h{w}V{zero}Z",
+                x = x,
+                zero = zero,
+                y = scale.y(*v),
+                w = slot - gap
+            )
+            .unwrap()
         }
         path_str
     }
 }
 
+// Points of the line chart split into runs of consecutive finite values.
+fn line_runs(data: &[f32], height: usize, width: usize, range: ChartRange) -> Vec<Vec<(f32, f32)>> {
+    let scale = YScale::new(data, range, height, false);
+    let width = width as f32;
+
+    // A single value is drawn as a flat line across the chart
+    if let [v] = data {
+        return match v.is_finite() {
+            true => vec![vec![(0.0, scale.y(*v)), (width, scale.y(*v))]],
+            false => Vec::new(),
+        };
+    }
+
+    let x_offset = width / (data.len() as f32 - 1.0);
+
+    let mut runs = Vec::new();
+    let mut run = Vec::new();
+    for (i, v) in data.iter().enumerate() {
+        if v.is_finite() {
+            run.push((i as f32 * x_offset, scale.y(*v)));
+        } else if !run.is_empty() {
+            runs.push(std::mem::take(&mut run));
+        }
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+    runs
+}
+
 #[derive(Default)]
 pub(super) struct ContentSize {
     pub(super) x: usize,
@@ -130,7 +191,7 @@ pub(super) trait BadgeContentSize {
     ) -> ContentSize;
 }
 
-impl<'a> BadgeContentSize for &'a [f32] {
+impl BadgeContentSize for &[f32] {
     #[inline]
     fn content_size(&self, height: usize, width: usize, padding: usize, _: usize) -> ContentSize {
         ContentSize {
@@ -141,7 +202,7 @@ impl<'a> BadgeContentSize for &'a [f32] {
     }
 }
 
-impl<'a> BadgeContentSize for &'a str {
+impl BadgeContentSize for &str {
     #[inline]
     fn content_size(
         &self,
@@ -160,29 +221,138 @@ impl<'a> BadgeContentSize for &'a str {
 
 #[cfg(test)]
 mod tests {
-    use super::{SvgPath, TextWidth};
+    use super::{ChartRange, SvgPath, TextWidth};
+    use crate::badge::font::DEFAULT_FONTS;
 
     #[test]
     fn content_str_width() {
         let s = "Hello";
-        let bc = s.text_width(20.);
+        let bc = s.text_width(20., &DEFAULT_FONTS);
         assert!(bc > 0);
     }
     #[test]
     fn content_text_has_width() {
-        let text = "".text_width(20.);
+        let text = "".text_width(20., &DEFAULT_FONTS);
         assert_eq!(text, 0);
-        let text = "npm".text_width(20.);
-        assert_eq!(text, 46);
-        let text = "long text".text_width(20.);
+        let text = "npm".text_width(20., &DEFAULT_FONTS);
+        assert_eq!(text, 45);
+        let text = "long text".text_width(20., &DEFAULT_FONTS);
         assert_eq!(text, 90);
     }
 
+    #[test]
+    fn kerned_pairs_are_narrower() {
+        let width = |s: &str| s.text_width(20., &DEFAULT_FONTS);
+        assert!(width("AV") < width("A") + width("V"));
+        assert!(width("To") < width("T") + width("o"));
+    }
+
+    #[test]
+    fn bars_generate() {
+        let d: &[f32; 4] = &[2., 4., 0., 1.];
+        let path = &d.svg_bars(20, 100, ChartRange::default());
+
+        assert_eq!(
+            path,
+            "M1.25 20V10h22.5V20ZM26.25 20V0h22.5V20ZM51.25 20V20h22.5V20ZM76.25 20V15h22.5V20Z"
+        )
+    }
+
+    #[test]
+    fn bars_grow_down_from_zero() {
+        let d: &[f32; 2] = &[-1., 1.];
+        let path = &d.svg_bars(20, 100, ChartRange::default());
+
+        assert_eq!(path, "M2.5 10V20h45V10ZM52.5 10V0h45V10Z")
+    }
+
+    #[test]
+    fn bars_all_zero() {
+        let d: &[f32; 2] = &[0., 0.];
+        let path = &d.svg_bars(20, 100, ChartRange::default());
+
+        assert_eq!(path, "M2.5 20V20h45V20ZM52.5 20V20h45V20Z")
+    }
+
     #[test]
     fn path_generate() {
         let d: &[f32; 4] = &[2., 4., 3., 2.];
-        let path = &d.svg_path(20, 100);
+        let path = &d.svg_path(20, 100, ChartRange::default());
+
+        assert_eq!(path, "M0 20L33.333332 0L66.666664 10L100 20")
+    }
+
+    #[test]
+    fn path_negative_values() {
+        let d: &[f32; 3] = &[-3., -1., -2.];
+        let path = &d.svg_path(20, 100, ChartRange::default());
+
+        assert_eq!(path, "M0 20L50 0L100 10")
+    }
+
+    #[test]
+    fn path_constant_is_flat() {
+        for d in [[0., 0., 0.], [5., 5., 5.]] {
+            let path = &d.svg_path(20, 100, ChartRange::default());
+            assert_eq!(path, "M0 10L50 10L100 10")
+        }
+    }
+
+    #[test]
+    fn path_single_point() {
+        let path = &[5.].svg_path(20, 100, ChartRange::default());
+        assert_eq!(path, "M0 10L100 10");
+
+        let path = &[f32::NAN].svg_path(20, 100, ChartRange::default());
+        assert_eq!(path, "");
+        assert_eq!([].svg_path(20, 100, ChartRange::default()), "");
+    }
+
+    #[test]
+    fn path_gaps_on_non_finite() {
+        let d: &[f32; 5] = &[0., 2., f32::NAN, f32::INFINITY, 1.];
+        let path = &d.svg_path(20, 100, ChartRange::default());
+        assert_eq!(path, "M0 20L25 0M100 10");
+
+        let area = &d.svg_area(20, 100, ChartRange::default());
+        assert_eq!(area, "M0 20L0 20L25 0L25 20ZM100 20L100 10L100 20Z");
+    }
+
+    #[test]
+    fn path_range_override() {
+        let range = ChartRange {
+            min: Some(0.),
+            max: Some(10.),
+        };
+        let d: &[f32; 3] = &[5., 10., 20.];
+        let path = &d.svg_path(20, 100, range);
 
-        assert_eq!(path, "M0 10L0 10L33.333332 0L66.666664 5L100 10")
+        assert_eq!(path, "M0 10L50 0L100 0")
+    }
+
+    #[test]
+    fn path_has_no_invalid_numbers() {
+        let series: [&[f32]; 6] = [
+            &[-3., -1., -2.],
+            &[0., 0., 0.],
+            &[5.],
+            &[f32::NAN, 1., f32::NEG_INFINITY],
+            &[f32::MAX, f32::MIN],
+            &[],
+        ];
+        for d in series {
+            for path in [
+                d.svg_path(20, 100, ChartRange::default()),
+                d.svg_area(20, 100, ChartRange::default()),
+                d.svg_bars(20, 100, ChartRange::default()),
+            ] {
+                assert!(
+                    !path.contains("NaN") && !path.contains("inf"),
+                    "{:?} => {}",
+                    d,
+                    path
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,322 @@
+use crate::FontError;
+use ab_glyph::{Font, FontVec, GlyphId};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Arc, sync::RwLock};
+
+pub(crate) static FONT_DATA: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/resx/Verdana.ttf"));
+
+const FONT_FAMILY: &str = "Verdana";
+
+// Entries per metrics cache. A full cache is cleared, so text with many
+// distinct glyphs can't grow it without bound.
+const CACHE_LIMIT: usize = 4096;
+
+static EMBEDDED: Lazy<Arc<LoadedFont>> = Lazy::new(|| {
+    let font = LoadedFont::new(FONT_FAMILY, FONT_DATA.to_vec()).expect("Error constructing Font");
+    Arc::new(font)
+});
+
+pub(super) static DEFAULT_FONTS: Lazy<FontSet> = Lazy::new(FontSet::default);
+
+pub(crate) struct LoadedFont {
+    pub(crate) family: String,
+    pub(crate) font: FontVec,
+    units_per_em: f32,
+    // Advances and kerning in em for ASCII, when the font has all of it
+    ascii: Option<Box<AsciiMetrics>>,
+    // Non-ASCII advances in em keyed by glyph
+    advance_cache: RwLock<HashMap<GlyphId, f32>>,
+    // Kerning in em keyed by glyph pair
+    kern_cache: RwLock<HashMap<(GlyphId, GlyphId), f32>>,
+}
+
+struct AsciiMetrics {
+    advances: [f32; 128],
+    kerning: [[f32; 128]; 128],
+}
+
+impl LoadedFont {
+    fn new(family: &str, data: Vec<u8>) -> Result<Self, FontError> {
+        let font = FontVec::try_from_vec(data).map_err(|_| FontError::Invalid)?;
+        let units_per_em = font.units_per_em().unwrap_or(1000.0);
+
+        let has_ascii = (b' '..=b'~').all(|b| font.glyph_id(b as char) != GlyphId(0));
+        let ascii = has_ascii.then(|| {
+            let ids: Vec<GlyphId> = (0u8..=127u8).map(|b| font.glyph_id(b as char)).collect();
+            let mut metrics = Box::new(AsciiMetrics {
+                advances: [0.0; 128],
+                kerning: [[0.0; 128]; 128],
+            });
+            for (advance, &id) in metrics.advances.iter_mut().zip(&ids) {
+                *advance = font.h_advance_unscaled(id) / units_per_em;
+            }
+            for a in b' '..=b'~' {
+                for b in b' '..=b'~' {
+                    let kern = font.kern_unscaled(ids[a as usize], ids[b as usize]);
+                    metrics.kerning[a as usize][b as usize] = kern / units_per_em;
+                }
+            }
+            metrics
+        });
+
+        Ok(LoadedFont {
+            family: family.to_string(),
+            font,
+            units_per_em,
+            ascii,
+            advance_cache: RwLock::new(HashMap::new()),
+            kern_cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    #[inline]
+    fn has_glyph(&self, c: char) -> bool {
+        self.font.glyph_id(c) != GlyphId(0)
+    }
+
+    /// Scale from font units to px at `font_size`.
+    #[inline]
+    pub(super) fn scale(&self, font_size: f32) -> f32 {
+        font_size / self.units_per_em
+    }
+
+    /// Horizontal advance of `id` in em.
+    fn advance(&self, id: GlyphId) -> f32 {
+        if let Some(&w) = self.advance_cache.read().unwrap().get(&id) {
+            return w;
+        }
+        let advance = self.font.h_advance_unscaled(id) / self.units_per_em;
+        let mut cache = self.advance_cache.write().unwrap();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        *cache.entry(id).or_insert(advance)
+    }
+
+    /// Kerning between `first` and `second` in em.
+    fn kerning(&self, first: GlyphId, second: GlyphId) -> f32 {
+        let key = (first, second);
+        if let Some(&k) = self.kern_cache.read().unwrap().get(&key) {
+            return k;
+        }
+
+        // Look up without holding the lock
+        let kern = self.font.kern_unscaled(first, second) / self.units_per_em;
+        let mut cache = self.kern_cache.write().unwrap();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        *cache.entry(key).or_insert(kern)
+    }
+}
+
+/// Fonts used to measure badge text, in fallback order.
+///
+/// Each character is measured with the first font that has a glyph for it.
+/// The families are written to the svg `font-family`, so viewers pick the
+/// same fonts when they are installed.
+///
+/// ```no_run
+/// use badgeland::{Badge, FontSet};
+///
+/// let mut fonts = FontSet::new();
+/// fonts
+///     .add_font_file("Inter", "fonts/Inter-Regular.ttf")?
+///     .add_font_file("Noto Sans CJK JP", "fonts/NotoSansCJKjp-Regular.otf")?;
+///
+/// let mut badge = Badge::new();
+/// badge.subject("ビルド").fonts(&fonts);
+/// let svg = badge.text("passing").to_string();
+/// # Ok::<(), badgeland::FontError>(())
+/// ```
+#[derive(Clone)]
+pub struct FontSet {
+    fonts: Vec<Arc<LoadedFont>>,
+}
+
+impl Default for FontSet {
+    /// The embedded Verdana only.
+    fn default() -> Self {
+        FontSet {
+            fonts: vec![EMBEDDED.clone()],
+        }
+    }
+}
+
+impl fmt::Debug for FontSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.families()).finish()
+    }
+}
+
+impl FontSet {
+    /// Empty set. Text is measured with the embedded Verdana until a font is added.
+    pub fn new() -> Self {
+        FontSet { fonts: Vec::new() }
+    }
+
+    /// Add a TTF / OTF font. `family` is the name written to the svg.
+    pub fn add_font(&mut self, family: &str, data: Vec<u8>) -> Result<&mut Self, FontError> {
+        self.fonts.push(Arc::new(LoadedFont::new(family, data)?));
+        Ok(self)
+    }
+
+    pub fn add_font_file<P: AsRef<Path>>(
+        &mut self,
+        family: &str,
+        path: P,
+    ) -> Result<&mut Self, FontError> {
+        let data = fs::read(path)?;
+        self.add_font(family, data)
+    }
+
+    /// Add the embedded Verdana, e.g. as a fallback after a custom font.
+    pub fn add_embedded(&mut self) -> &mut Self {
+        self.fonts.push(EMBEDDED.clone());
+        self
+    }
+
+    pub fn families(&self) -> impl Iterator<Item = &str> {
+        self.loaded().iter().map(|f| f.family.as_str())
+    }
+
+    /// Value of the svg `font-family` attribute, ending with `sans-serif`.
+    pub(crate) fn css_family(&self) -> String {
+        let mut css = String::new();
+        for family in self.families() {
+            if family.contains(|c: char| !c.is_ascii_alphanumeric() && c != '-') {
+                css.push('\'');
+                css.extend(family.chars().filter(|&c| c != '\'' && c != '\\'));
+                css.push('\'');
+            } else {
+                css.push_str(family);
+            }
+            css.push(',');
+        }
+        css.push_str("sans-serif");
+        css
+    }
+
+    #[inline]
+    pub(crate) fn loaded(&self) -> &[Arc<LoadedFont>] {
+        match self.fonts.as_slice() {
+            [] => std::slice::from_ref(&EMBEDDED),
+            fonts => fonts,
+        }
+    }
+
+    /// Text width in px at `font_size`, from glyph advances and kerning.
+    pub(super) fn text_width(&self, s: &str, font_size: f32) -> f32 {
+        let fonts = self.loaded();
+
+        // Fast path for ASCII-only strings: use the precomputed tables
+        if let (true, Some(ascii)) = (s.is_ascii(), &fonts[0].ascii) {
+            let bytes = s.as_bytes();
+            let advances: f32 = bytes.iter().map(|&b| ascii.advances[b as usize]).sum();
+            let kerning: f32 = bytes
+                .windows(2)
+                .map(|p| ascii.kerning[p[0] as usize][p[1] as usize])
+                .sum();
+            return (advances + kerning) * font_size;
+        }
+
+        self.layout(s).1 * font_size
+    }
+
+    /// Glyphs of `s` with their x offset in em, advancing with kerning between
+    /// glyphs of the same font, and the total advance in em.
+    pub(super) fn layout(&self, s: &str) -> (Vec<(&LoadedFont, GlyphId, f32)>, f32) {
+        let mut glyphs = Vec::with_capacity(s.len());
+        let mut x = 0.0f32;
+        let mut prev: Option<(&LoadedFont, GlyphId)> = None;
+        for c in s.chars() {
+            let font = self.font_for(c);
+            let id = font.font.glyph_id(c);
+            if let Some((_, prev_id)) = prev.filter(|(f, _)| std::ptr::eq(*f, font)) {
+                x += font.kerning(prev_id, id);
+            }
+            glyphs.push((font, id, x));
+            x += font.advance(id);
+            prev = Some((font, id));
+        }
+        (glyphs, x)
+    }
+
+    /// First font with a glyph for `c`, the primary font if none has one.
+    #[inline]
+    pub(super) fn font_for(&self, c: char) -> &LoadedFont {
+        let fonts = self.loaded();
+        fonts.iter().find(|f| f.has_glyph(c)).unwrap_or(&fonts[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FontSet, CACHE_LIMIT, FONT_DATA};
+    use crate::{Badge, FontError};
+
+    #[test]
+    fn metrics_caches_are_bounded() {
+        let fonts = FontSet::default();
+        let text: String = ('\u{100}'..'\u{3000}').collect();
+        let width = fonts.text_width(&text, 20.);
+        assert_eq!(fonts.text_width(&text, 20.), width);
+
+        let font = &fonts.loaded()[0];
+        assert!(font.advance_cache.read().unwrap().len() <= CACHE_LIMIT);
+        assert!(font.kern_cache.read().unwrap().len() <= CACHE_LIMIT);
+    }
+
+    #[test]
+    fn empty_set_measures_with_embedded_font() {
+        let empty = FontSet::new();
+        let default = FontSet::default();
+        for s in ["npm", "long text", "ünïcödé", "漢字"] {
+            assert_eq!(empty.text_width(s, 20.), default.text_width(s, 20.));
+        }
+        assert_eq!(empty.families().collect::<Vec<_>>(), vec!["Verdana"]);
+    }
+
+    #[test]
+    fn ascii_fast_path_matches_layout() {
+        let fonts = FontSet::default();
+        for s in ["AV", "To", "Type LAVA", "npm", "long text"] {
+            let fast = fonts.text_width(s, 13.);
+            let laid_out = fonts.layout(s).1 * 13.;
+            assert!((fast - laid_out).abs() < 1e-3, "{s}: {fast} != {laid_out}");
+        }
+        // Kerning also applies outside the fast path
+        let width = |s| fonts.text_width(s, 13.);
+        assert!(width("AVé") < width("A") + width("V") + width("é"));
+    }
+
+    #[test]
+    fn css_family_lists_fallbacks() {
+        let mut fonts = FontSet::new();
+        fonts
+            .add_font("Brand Sans", FONT_DATA.to_vec())
+            .unwrap()
+            .add_embedded();
+        assert_eq!(fonts.css_family(), "'Brand Sans',Verdana,sans-serif");
+        assert_eq!(FontSet::default().css_family(), "Verdana,sans-serif");
+
+        let mut badge = Badge::new();
+        badge.subject("fonts").fonts(&fonts);
+        let svg = badge.text("text").to_string();
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let family = doc.descendants().find_map(|n| n.attribute("font-family"));
+        assert_eq!(family, Some("'Brand Sans',Verdana,sans-serif"));
+    }
+
+    #[test]
+    fn invalid_fonts_fail() {
+        let mut fonts = FontSet::new();
+        let err = fonts.add_font("Broken", vec![0; 16]).unwrap_err();
+        assert!(matches!(err, FontError::Invalid));
+        let err = fonts.add_font_file("Missing", "/no/such/font.ttf");
+        let err = err.unwrap_err();
+        assert!(matches!(err, FontError::Io(_)));
+        assert_eq!(fonts.families().count(), 1);
+    }
+}
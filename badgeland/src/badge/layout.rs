@@ -0,0 +1,97 @@
+/// Axis aligned box in badge (svg user unit) coordinates.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    #[inline]
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Flip horizontally around `axis`
+    #[inline]
+    pub(crate) fn mirror(&mut self, axis: usize) {
+        self.x = axis.saturating_sub(self.x + self.width);
+    }
+}
+
+/// Text anchor. Text is drawn centered on this point.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    #[inline]
+    pub(crate) fn mirror(&mut self, axis: usize) {
+        self.x = axis.saturating_sub(self.x);
+    }
+}
+
+/// Geometry of one part of a multi-segment badge.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct SegmentLayout {
+    pub rect: Rect,
+    pub icon: Option<Rect>,
+    pub text: Point,
+}
+
+/// Geometry of a rendered badge.
+///
+/// Every svg template is drawn from this layout, so the boxes here match the
+/// output of `Badge::to_string()` exactly.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct BadgeLayout {
+    pub width: usize,
+    pub height: usize,
+    pub font_size: f32,
+    /// Extra space after each glyph
+    pub letter_spacing: f32,
+    /// Corner radius
+    pub rx: usize,
+    /// Left part holding icon and subject, the right part for mirrored
+    /// badges. Zero width when neither is set.
+    pub subject: Rect,
+    /// Right part holding text, chart or every segment, the left part for
+    /// mirrored badges.
+    pub content: Rect,
+    pub icon: Option<Rect>,
+    pub subject_text: Option<Point>,
+    pub content_text: Option<Point>,
+    pub chart: Option<Rect>,
+    /// Filled part of a progress badge
+    pub progress: Option<Rect>,
+    /// One entry per segment, in segment order. Empty unless the badge has segments.
+    pub segments: Vec<SegmentLayout>,
+}
+
+impl BadgeLayout {
+    /// Flip every part horizontally around `axis`, for right-to-left badges.
+    pub(crate) fn mirror(&mut self, axis: usize) {
+        let rects = [&mut self.subject, &mut self.content].into_iter().chain(
+            self.icon
+                .iter_mut()
+                .chain(&mut self.chart)
+                .chain(&mut self.progress),
+        );
+        for rect in rects {
+            rect.mirror(axis);
+        }
+        for point in self.subject_text.iter_mut().chain(&mut self.content_text) {
+            point.mirror(axis);
+        }
+        for segment in &mut self.segments {
+            segment.rect.mirror(axis);
+            segment.text.mirror(axis);
+            if let Some(icon) = &mut segment.icon {
+                icon.mirror(axis);
+            }
+        }
+    }
+}
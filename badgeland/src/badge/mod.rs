@@ -1,16 +1,34 @@
+mod bidi;
+mod chart;
 mod content;
+mod font;
+mod layout;
+mod outline;
+#[cfg(feature = "raster")]
+mod raster;
+mod segment;
 mod size;
 mod style;
-use sailfish::TemplateOnce;
+use sailfish::{runtime::escape::escape_to_string, TemplateOnce};
 
+pub use chart::Chart;
+pub use font::FontSet;
+pub use layout::{BadgeLayout, Point, Rect, SegmentLayout};
+pub use segment::Segment;
 pub use size::Size;
 
 pub use style::Style;
 
-use super::{icons::Icon, Color};
-use content::{BadgeContentSize, ContentSize, SvgPath, TextWidth};
+#[cfg(feature = "raster")]
+pub use raster::RasterFormat;
+
+use super::{icons::Icon, Color, ColorScale, IdPrefixError};
+use bidi::is_rtl;
+use content::{BadgeContentSize, ChartRange, ContentSize, SvgPath, TextWidth};
 use core::{f32, fmt};
-use std::fmt::Debug;
+use font::DEFAULT_FONTS;
+use outline::text_path;
+use std::{borrow::Cow, fmt::Debug, ops::Deref};
 
 #[derive(Debug)]
 pub struct BadgeTypeInit;
@@ -18,6 +36,10 @@ pub struct BadgeTypeInit;
 pub struct BadgeTypeData<'a>(&'a [f32]);
 #[derive(Debug)]
 pub struct BadgeTypeText<'a>(&'a str);
+#[derive(Debug)]
+pub struct BadgeTypeSegments<'a>(&'a [Segment<'a>]);
+#[derive(Debug)]
+pub struct BadgeTypeProgress(f32);
 
 pub trait BadgeType<'a> {
     fn content(&self) -> BadgeContentType<'_>;
@@ -28,6 +50,9 @@ pub enum BadgeContentType<'a> {
     None,
     Text(&'a str),
     Data(&'a [f32]),
+    Segments(&'a [Segment<'a>]),
+    /// Percentage clamped to 0–100
+    Progress(f32),
 }
 
 impl BadgeContentType<'_> {
@@ -37,12 +62,20 @@ impl BadgeContentType<'_> {
     }
 
     #[inline]
-    fn content_size(&self, height: usize, padding: usize, font_size: f32) -> ContentSize {
+    fn content_size(
+        &self,
+        height: usize,
+        padding: usize,
+        text_width: impl Fn(&str) -> usize,
+    ) -> ContentSize {
         match self {
             BadgeContentType::Data(d) => d.content_size(height, height * 5, padding, 0),
-            BadgeContentType::Text(c) => {
-                c.content_size(height, c.text_width(font_size), padding, 0)
-            }
+            BadgeContentType::Progress(_) => ContentSize {
+                x: height * 5 / 2,
+                y: height / 2,
+                rw: height * 5,
+            },
+            BadgeContentType::Text(c) => c.content_size(height, text_width(c), padding, 0),
             _ => ContentSize::default(),
         }
     }
@@ -69,6 +102,20 @@ impl<'a> BadgeType<'a> for BadgeTypeText<'a> {
     }
 }
 
+impl<'a> BadgeType<'a> for BadgeTypeSegments<'a> {
+    #[inline]
+    fn content(&self) -> BadgeContentType<'a> {
+        BadgeContentType::Segments(self.0)
+    }
+}
+
+impl BadgeType<'_> for BadgeTypeProgress {
+    #[inline]
+    fn content(&self) -> BadgeContentType<'_> {
+        BadgeContentType::Progress(self.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct Badge<'a, S: BadgeType<'a> = BadgeTypeInit> {
     subject: Option<&'a str>,
@@ -77,9 +124,30 @@ pub struct Badge<'a, S: BadgeType<'a> = BadgeTypeInit> {
     icon: Option<Icon<'a>>,
     icon_color: Color,
     size: Size,
+    chart: Chart,
+    chart_range: ChartRange,
+    color_scale: Option<&'a ColorScale>,
+    id_prefix: IdPrefix<'a>,
+    fonts: Option<&'a FontSet>,
+    outline_text: bool,
+    max_width: Option<usize>,
+    mirror_segments: bool,
     content: S,
 }
 
+#[derive(Debug, Copy, Clone)]
+enum IdPrefix<'a> {
+    None,
+    Explicit(&'a str),
+    Hashed,
+}
+
+impl<'a> Default for Badge<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Badge<'a> {
     pub fn new() -> Self {
         Badge {
@@ -89,6 +157,14 @@ impl<'a> Badge<'a> {
             icon: None,
             icon_color: Color::white(),
             size: Size::Small,
+            chart: Chart::Line,
+            chart_range: ChartRange::default(),
+            color_scale: None,
+            id_prefix: IdPrefix::None,
+            fonts: None,
+            outline_text: false,
+            max_width: None,
+            mirror_segments: false,
             content: BadgeTypeInit,
         }
     }
@@ -110,8 +186,9 @@ impl<'a> Badge<'a, BadgeTypeInit> {
         self
     }
 
+    /// Custom heights are clamped to [`Size::CUSTOM_RANGE`].
     pub fn size(&mut self, size: Size) -> &mut Self {
-        self.size = size;
+        self.size = size.clamped();
         self
     }
 
@@ -120,8 +197,93 @@ impl<'a> Badge<'a, BadgeTypeInit> {
         self
     }
 
+    /// Chart kind used by data badges
+    pub fn chart(&mut self, chart: Chart) -> &mut Self {
+        self.chart = chart;
+        self
+    }
+
+    /// Value drawn at the bottom of the chart. Defaults to the data minimum.
+    pub fn y_min(&mut self, min: f32) -> &mut Self {
+        self.chart_range.min = Some(min);
+        self
+    }
+
+    /// Value drawn at the top of the chart. Defaults to the data maximum.
+    pub fn y_max(&mut self, max: f32) -> &mut Self {
+        self.chart_range.max = Some(max);
+        self
+    }
+
+    /// Pick the badge color from its value: the percentage of a progress
+    /// badge, the number text starts with (`87.5%`, `12s`) or the last
+    /// finite data point. Falls back to `color` when the scale has no color
+    /// for the value.
+    ///
+    /// ```
+    /// use badgeland::{Badge, ColorScale};
+    ///
+    /// let scale: ColorScale = "0:red,50:yellow,80:green".parse().unwrap();
+    /// let mut badge = Badge::new();
+    /// badge.subject("coverage").color_scale(&scale);
+    /// let svg = badge.progress(87.5).to_string();
+    /// ```
+    pub fn color_scale(&mut self, scale: &'a ColorScale) -> &mut Self {
+        self.color_scale = Some(scale);
+        self
+    }
+
+    /// Prefix every svg element id and reference, so several badges can be
+    /// inlined in one html page. Must be a letter followed by letters, digits,
+    /// `-` or `_`, since it is also written into css selectors.
+    pub fn id_prefix(&mut self, prefix: &'a str) -> Result<&mut Self, IdPrefixError> {
+        if !valid_id_prefix(prefix) {
+            return Err(IdPrefixError);
+        }
+        self.id_prefix = IdPrefix::Explicit(prefix);
+        Ok(self)
+    }
+
+    /// Like [`id_prefix`](Self::id_prefix) with a prefix derived from a hash
+    /// of the badge, so identical badges share ids and different ones don't.
+    pub fn hashed_ids(&mut self) -> &mut Self {
+        self.id_prefix = IdPrefix::Hashed;
+        self
+    }
+
+    /// Fonts to measure text with and name in the svg. Defaults to the
+    /// embedded Verdana.
+    pub fn fonts(&mut self, fonts: &'a FontSet) -> &mut Self {
+        self.fonts = Some(fonts);
+        self
+    }
+
+    /// Draw text as `<path>` outlines of the font glyphs instead of `<text>`,
+    /// so the badge looks the same whether or not the viewer has the font.
+    pub fn outline_text(&mut self) -> &mut Self {
+        self.outline_text = true;
+        self
+    }
+
+    /// Shorten the subject and text content with an ellipsis so the badge fits
+    /// in `width` px, as far as shortening them can. Segment text, padding and
+    /// icons are kept, so the badge can still end up wider. The full text
+    /// stays in the title and label.
+    pub fn max_width(&mut self, width: usize) -> &mut Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Lay a segment badge out right to left, for right-to-left languages:
+    /// the subject and its icon at the right edge, then the segments in order
+    /// towards the left.
+    pub fn mirror_segments(&mut self) -> &mut Self {
+        self.mirror_segments = true;
+        self
+    }
+
     pub fn icon_color(&mut self, c: Color) -> &mut Self {
-        if let Some(_) = &self.icon {
+        if self.icon.is_some() {
             self.icon_color = c;
         }
         self
@@ -135,6 +297,14 @@ impl<'a> Badge<'a, BadgeTypeInit> {
             icon: self.icon,
             icon_color: self.icon_color,
             size: self.size,
+            chart: self.chart,
+            chart_range: self.chart_range,
+            color_scale: self.color_scale,
+            id_prefix: self.id_prefix,
+            fonts: self.fonts,
+            outline_text: self.outline_text,
+            max_width: self.max_width,
+            mirror_segments: self.mirror_segments,
             content: BadgeTypeText(text),
         }
     }
@@ -147,24 +317,125 @@ impl<'a> Badge<'a, BadgeTypeInit> {
             icon: self.icon,
             icon_color: self.icon_color,
             size: self.size,
+            chart: self.chart,
+            chart_range: self.chart_range,
+            color_scale: self.color_scale,
+            id_prefix: self.id_prefix,
+            fonts: self.fonts,
+            outline_text: self.outline_text,
+            max_width: self.max_width,
+            mirror_segments: self.mirror_segments,
             content: BadgeTypeData(data),
         }
     }
+
+    pub fn segments(self, segments: &'a [Segment<'a>]) -> Badge<'a, BadgeTypeSegments<'a>> {
+        Badge {
+            subject: self.subject,
+            color: self.color,
+            style: self.style,
+            icon: self.icon,
+            icon_color: self.icon_color,
+            size: self.size,
+            chart: self.chart,
+            chart_range: self.chart_range,
+            color_scale: self.color_scale,
+            id_prefix: self.id_prefix,
+            fonts: self.fonts,
+            outline_text: self.outline_text,
+            max_width: self.max_width,
+            mirror_segments: self.mirror_segments,
+            content: BadgeTypeSegments(segments),
+        }
+    }
+
+    /// Bar filled to `percent` of the content area with the percentage on
+    /// top. Values are clamped to 0–100, non-finite values count as 0.
+    pub fn progress(self, percent: f32) -> Badge<'a, BadgeTypeProgress> {
+        let percent = if percent.is_finite() {
+            percent.clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        Badge {
+            subject: self.subject,
+            color: self.color,
+            style: self.style,
+            icon: self.icon,
+            icon_color: self.icon_color,
+            size: self.size,
+            chart: self.chart,
+            chart_range: self.chart_range,
+            color_scale: self.color_scale,
+            id_prefix: self.id_prefix,
+            fonts: self.fonts,
+            outline_text: self.outline_text,
+            max_width: self.max_width,
+            mirror_segments: self.mirror_segments,
+            content: BadgeTypeProgress(percent),
+        }
+    }
 }
 
 impl<'a, T: BadgeType<'a>> Badge<'a, T> {
     #[inline]
     fn height(&self) -> usize {
-        match self.size {
-            Size::Small => 20,
-            Size::Medium => 30,
-            Size::Large => 40,
+        let height = self.size.height();
+        match self.style {
+            Style::ForTheBadge => height * 7 / 5,
+            _ => height,
         }
     }
 
+    #[inline]
+    fn font_set(&self) -> &FontSet {
+        self.fonts.unwrap_or(&DEFAULT_FONTS)
+    }
+
     #[inline]
     fn font_size(&self) -> f32 {
-        self.height() as f32 * SVG_FONT_MULTIPLIER
+        let multiplier = match self.style {
+            Style::ForTheBadge => FOR_THE_BADGE_FONT_MULTIPLIER,
+            _ => SVG_FONT_MULTIPLIER,
+        };
+        self.height() as f32 * multiplier
+    }
+
+    #[inline]
+    fn letter_spacing(&self) -> f32 {
+        match self.style {
+            Style::ForTheBadge => self.font_size() * FOR_THE_BADGE_LETTER_SPACING,
+            _ => 0.0,
+        }
+    }
+
+    /// `s` as drawn: uppercase in the for-the-badge style.
+    #[inline]
+    fn cased<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        match self.style {
+            Style::ForTheBadge if s.chars().any(char::is_lowercase) => s.to_uppercase().into(),
+            _ => s.into(),
+        }
+    }
+
+    /// Width of `s` as drawn, including letter spacing and bold text.
+    fn text_width(&self, s: &str) -> usize {
+        let (fonts, font_size) = (self.font_set(), self.font_size());
+        match self.style {
+            Style::ForTheBadge => {
+                let s = self.cased(s);
+                let width = s.as_ref().text_width(font_size, fonts) as f32;
+                let spacing = self.letter_spacing() * s.trim().chars().count() as f32;
+                // Outlines are traced from the regular face, so aren't bold
+                let bold = if self.outline_text {
+                    1.0
+                } else {
+                    BOLD_WIDTH_FACTOR
+                };
+                (width * bold + spacing).ceil() as usize
+            }
+            _ => s.text_width(font_size, fonts),
+        }
     }
 
     #[inline]
@@ -172,28 +443,27 @@ impl<'a, T: BadgeType<'a>> Badge<'a, T> {
         if self.icon.is_none() {
             return (0, 0);
         }
+        self.icon_dims()
+    }
+
+    #[inline]
+    fn icon_dims(&self) -> (usize, usize) {
         match self.size {
             Size::Large => (30, 10),
             Size::Medium => (20, 8),
             Size::Small => (15, 5),
+            Size::Custom(h) => (h as usize * 3 / 4, h as usize / 4),
         }
     }
 
     #[inline]
-    fn subject_size(&self, padding: usize) -> ContentSize {
+    fn subject_size(&self, subject: Option<&str>, padding: usize) -> ContentSize {
         let height = self.height();
 
-        let font_size = self.font_size();
-
         let (icon_width, x_offset) = self.icon_size();
 
-        match self.subject {
-            Some(s) => s.content_size(
-                height,
-                s.text_width(font_size),
-                padding,
-                x_offset + icon_width,
-            ),
+        match subject {
+            Some(s) => s.content_size(height, self.text_width(s), padding, x_offset + icon_width),
             None if self.icon.is_some() => ContentSize {
                 rw: icon_width + x_offset * 2,
                 x: x_offset,
@@ -203,86 +473,137 @@ impl<'a, T: BadgeType<'a>> Badge<'a, T> {
         }
     }
 
+    #[inline]
+    fn segment_size(&self, segment: &Segment, padding: usize) -> ContentSize {
+        let (icon_width, x_offset) = match segment.icon {
+            Some(_) => self.icon_dims(),
+            None => (0, 0),
+        };
+        segment.text.content_size(
+            self.height(),
+            self.text_width(segment.text),
+            padding,
+            x_offset + icon_width,
+        )
+    }
+
+    #[inline]
+    fn scaled_color(&self, content: &BadgeContentType) -> Cow<'_, Color> {
+        let value = match content {
+            BadgeContentType::Progress(p) => Some(*p),
+            BadgeContentType::Text(t) => leading_number(t),
+            BadgeContentType::Data(d) => d.iter().rev().copied().find(|v| v.is_finite()),
+            _ => None,
+        };
+        self.color_scale
+            .zip(value)
+            .and_then(|(scale, v)| scale.color(v))
+            .map_or(Cow::Borrowed(&self.color), Cow::Owned)
+    }
+
     #[inline]
     fn rx(&self) -> usize {
-        match self.size {
-            Size::Medium => 6,
-            Size::Large => 9,
+        match (self.style, self.size) {
+            (Style::ForTheBadge, _) => 0,
+            (Style::Plastic, Size::Medium) => 8,
+            (Style::Plastic, Size::Large) => 11,
+            (Style::Plastic, Size::Custom(h)) => h as usize / 4,
+            (Style::Plastic, _) => 5,
+            (_, Size::Medium) => 6,
+            (_, Size::Large) => 9,
+            (_, Size::Custom(h)) => h as usize * 3 / 20,
             _ => 3,
         }
     }
 }
 
-#[derive(TemplateOnce)]
-#[template(path = "classic.stpl")]
-struct ClassicTemplate<'a> {
-    width: usize,
-    height: usize,
-    font_size: f32,
-    x_offset: usize,
-    rx: usize,
+/// Everything a badge template draws from, the same for every style.
+struct TemplateContext<'a> {
+    layout: BadgeLayout,
 
-    icon: Option<(&'a Icon<'a>, &'a Color)>,
+    icon: Option<&'a Icon<'a>>,
 
-    icon_width: usize,
+    icon_color: &'a Color,
 
     color: &'a Color,
 
     content: BadgeContentType<'a>,
-    content_size: ContentSize,
 
-    subject: Option<&'a str>,
-    subject_size: ContentSize,
-}
+    chart: Chart,
 
-#[derive(TemplateOnce)]
-#[template(path = "flat.stpl")]
-struct FlatTemplate<'a> {
-    width: usize,
-    height: usize,
-    font_size: f32,
-    x_offset: usize,
+    chart_range: ChartRange,
 
-    icon: Option<(&'a Icon<'a>, &'a Color)>,
+    subject: Option<&'a str>,
 
-    icon_width: usize,
+    id_prefix: &'a str,
 
-    color: &'a Color,
+    a11y_label: String,
 
-    content: BadgeContentType<'a>,
-    content_size: ContentSize,
+    font_family: String,
 
-    subject: Option<&'a str>,
-    subject_size: ContentSize,
+    outline: Option<&'a FontSet>,
 }
 
+// A style only picks its template file, fields are read through `Deref`
 #[derive(TemplateOnce)]
-#[template(path = "social.stpl")]
-struct SocialTemplate<'a> {
-    width: usize,
-    height: usize,
-    font_size: f32,
-    x_offset: usize,
-    rx: usize,
+#[template(path = "classic.stpl")]
+struct ClassicTemplate<'a>(TemplateContext<'a>);
 
-    icon: Option<(&'a Icon<'a>, Option<&'a Color>)>,
+#[derive(TemplateOnce)]
+#[template(path = "flat.stpl")]
+struct FlatTemplate<'a>(TemplateContext<'a>);
 
-    icon_width: usize,
+#[derive(TemplateOnce)]
+#[template(path = "plastic.stpl")]
+struct PlasticTemplate<'a>(TemplateContext<'a>);
 
-    color: &'a Color,
+#[derive(TemplateOnce)]
+#[template(path = "for_the_badge.stpl")]
+struct ForTheBadgeTemplate<'a>(TemplateContext<'a>);
 
-    content: BadgeContentType<'a>,
-    content_size: ContentSize,
+#[derive(TemplateOnce)]
+#[template(path = "social.stpl")]
+struct SocialTemplate<'a>(TemplateContext<'a>);
 
-    subject: Option<&'a str>,
-    subject_size: ContentSize,
+macro_rules! deref_context {
+    ($($name:ident),*) => {$(
+        impl<'a> Deref for $name<'a> {
+            type Target = TemplateContext<'a>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+    )*};
 }
 
+deref_context!(
+    ClassicTemplate,
+    FlatTemplate,
+    PlasticTemplate,
+    ForTheBadgeTemplate,
+    SocialTemplate
+);
+
 const SVG_FONT_MULTIPLIER: f32 = 0.65;
 
+const FOR_THE_BADGE_FONT_MULTIPLIER: f32 = 0.4;
+
+// In em
+const FOR_THE_BADGE_LETTER_SPACING: f32 = 0.1;
+
+// Bold text is measured with the regular font, Verdana Bold is about this much wider
+const BOLD_WIDTH_FACTOR: f32 = 1.1;
+
 impl<'a, T: BadgeType<'a>> Badge<'a, T> {
-    #[inline]
-    fn render(&self) -> String {
+    /// Compute the geometry of the badge without rendering it.
+    pub fn layout(&self) -> BadgeLayout {
+        let (subject, text) = self.fitted_text();
+        let content = with_text(self.content.content(), text.as_deref());
+        self.layout_with(subject.as_deref(), &content)
+    }
+
+    fn layout_with(&self, subject: Option<&str>, content: &BadgeContentType) -> BadgeLayout {
         let height = self.height();
 
         let font_size = self.font_size();
@@ -291,11 +612,30 @@ impl<'a, T: BadgeType<'a>> Badge<'a, T> {
 
         let (icon_width, x_offset) = self.icon_size();
 
-        let subject_size = self.subject_size(padding);
+        let subject_size = self.subject_size(subject, padding);
 
-        let content = self.content.content();
+        let segment_sizes: Vec<ContentSize> = match content {
+            BadgeContentType::Segments(segments) => segments
+                .iter()
+                .map(|s| self.segment_size(s, padding))
+                .collect(),
+            _ => Vec::new(),
+        };
 
-        let content_size = content.content_size(height, padding, font_size);
+        let content_size = match content {
+            BadgeContentType::Segments(_) => ContentSize {
+                rw: segment_sizes.iter().map(|s| s.rw).sum(),
+                ..ContentSize::default()
+            },
+            _ => content.content_size(height, padding, |s| self.text_width(s)),
+        };
+
+        // Social style separates subject and content with a 6px notch
+        let gap = if matches!(self.style, Style::Social) {
+            6
+        } else {
+            0
+        };
 
         let mut width = subject_size.rw + content_size.rw;
 
@@ -304,72 +644,325 @@ impl<'a, T: BadgeType<'a>> Badge<'a, T> {
             width += 7; // approximates 6px notch + 1px stroke
         }
 
-        let rx = self.rx();
+        let content_rect = Rect {
+            x: subject_size.rw + gap,
+            y: 0,
+            width: content_size.rw,
+            height,
+        };
+
+        let mut layout = BadgeLayout {
+            width,
+            height,
+            font_size,
+            letter_spacing: self.letter_spacing(),
+            rx: self.rx(),
+            subject: Rect {
+                x: 0,
+                y: 0,
+                width: subject_size.rw,
+                height,
+            },
+            content: content_rect,
+            icon: self.icon.as_ref().map(|_| Rect {
+                x: x_offset,
+                y: (height - icon_width) / 2,
+                width: icon_width,
+                height: icon_width,
+            }),
+            subject_text: subject.map(|_| Point {
+                x: subject_size.x,
+                y: subject_size.y,
+            }),
+            content_text: match content {
+                BadgeContentType::Text(_) | BadgeContentType::Progress(_) => Some(Point {
+                    x: content_rect.x + content_size.x,
+                    y: content_size.y,
+                }),
+                _ => None,
+            },
+            chart: match content {
+                BadgeContentType::Data(_) => Some(Rect {
+                    x: content_rect.x,
+                    y: 0,
+                    width: height * 5,
+                    height,
+                }),
+                _ => None,
+            },
+            progress: match content {
+                BadgeContentType::Progress(p) => Some(Rect {
+                    width: (content_rect.width as f32 * p / 100.0).round() as usize,
+                    ..content_rect
+                }),
+                _ => None,
+            },
+            segments: match content {
+                BadgeContentType::Segments(segments) => {
+                    let (seg_icon_width, seg_x_offset) = self.icon_dims();
+                    let mut x = content_rect.x;
+                    segments
+                        .iter()
+                        .zip(segment_sizes)
+                        .map(|(segment, size)| {
+                            let seg = SegmentLayout {
+                                rect: Rect {
+                                    x,
+                                    y: 0,
+                                    width: size.rw,
+                                    height,
+                                },
+                                icon: segment.icon.as_ref().map(|_| Rect {
+                                    x: x + seg_x_offset,
+                                    y: (height - seg_icon_width) / 2,
+                                    width: seg_icon_width,
+                                    height: seg_icon_width,
+                                }),
+                                text: Point {
+                                    x: x + size.x,
+                                    y: size.y,
+                                },
+                            };
+                            x += size.rw;
+                            seg
+                        })
+                        .collect()
+                }
+                _ => Vec::new(),
+            },
+        };
+
+        if self.mirror_segments && matches!(content, BadgeContentType::Segments(_)) {
+            layout.mirror(content_rect.x + content_rect.width);
+        }
+        layout
+    }
+
+    /// Subject and text content, shortened with an ellipsis to fit `max_width`.
+    fn fitted_text(&self) -> (Option<Cow<'_, str>>, Option<Cow<'_, str>>) {
+        let content = self.content.content();
+        let subject = self.subject;
+        let text = match content {
+            BadgeContentType::Text(t) => Some(t),
+            _ => None,
+        };
+
+        let excess = self.max_width.map_or(0, |max_width| {
+            let width = self.layout_with(subject, &content).width;
+            width.saturating_sub(max_width)
+        });
+        let subject = subject.map(|s| self.cased(s));
+        let text = text.map(|t| self.cased(t));
+        if excess == 0 {
+            return (subject, text);
+        }
 
-        let icon = self.icon.as_ref().map(|i| (i, &self.icon_color));
+        let width = |s: &Option<Cow<str>>| s.as_deref().map_or(0, |s| self.text_width(s));
+        let (subject_width, text_width) = (width(&subject), width(&text));
+
+        // Split the space evenly, giving what one side doesn't need to the other
+        let budget = (subject_width + text_width).saturating_sub(excess);
+        let half = budget / 2;
+        let (subject_budget, text_budget) = if subject_width <= half {
+            (subject_width, budget - subject_width)
+        } else if text_width <= half {
+            (budget - text_width, text_width)
+        } else {
+            (half, budget - half)
+        };
+
+        (
+            subject.map(|s| ellipsize(s, subject_budget, |s| self.text_width(s))),
+            text.map(|t| ellipsize(t, text_budget, |t| self.text_width(t))),
+        )
+    }
 
-        match self.style {
-            Style::Classic => {
-                let tpl = ClassicTemplate {
-                    width,
-                    height,
-                    font_size,
-                    x_offset,
-                    rx,
-                    icon,
-                    icon_width,
-                    color: &self.color,
-                    content,
-                    content_size,
-                    subject: self.subject,
-                    subject_size,
-                };
-                tpl.render_once().unwrap()
-            }
-            Style::Flat => {
-                let tpl = FlatTemplate {
-                    width,
-                    height,
-                    font_size,
-                    x_offset,
-                    icon,
-                    icon_width,
-                    color: &self.color,
-                    content,
-                    content_size,
-                    subject: self.subject,
-                    subject_size,
-                };
-                tpl.render_once().unwrap()
+    #[inline]
+    fn render(&self) -> String {
+        match self.id_prefix {
+            IdPrefix::None => self.render_with(""),
+            IdPrefix::Explicit(p) => self.render_with(p),
+            IdPrefix::Hashed => {
+                let prefix = format!("b{:08x}-", fnv1a(self.render_with("").as_bytes()));
+                self.render_with(&prefix)
             }
-            Style::Social => {
-                let social_icon = self.icon.as_ref().map(|i| {
-                    if self.icon_color == Color::white() {
-                        (i, None)
-                    } else {
-                        (i, Some(&self.icon_color))
-                    }
-                });
-                let tpl = SocialTemplate {
-                    width,
-                    height,
-                    font_size,
-                    x_offset,
-                    rx,
-                    icon: social_icon,
-                    icon_width,
-                    color: &self.color,
-                    content,
-                    content_size,
-                    subject: self.subject,
-                    subject_size,
-                };
-                tpl.render_once().unwrap()
+        }
+    }
+
+    fn render_with(&self, id_prefix: &str) -> String {
+        let a11y_label = a11y_label(self.subject, &self.content.content());
+
+        let (subject, text) = self.fitted_text();
+        let subject = subject.as_deref();
+        let content = with_text(self.content.content(), text.as_deref());
+
+        // Segment text is drawn as given, so uppercase it here
+        let cased: Vec<Cow<str>>;
+        let cased_segments: Vec<Segment>;
+        let content = match content {
+            BadgeContentType::Segments(segments) if self.style == Style::ForTheBadge => {
+                cased = segments.iter().map(|s| self.cased(s.text)).collect();
+                cased_segments = segments
+                    .iter()
+                    .zip(&cased)
+                    .map(|(s, text)| Segment {
+                        text,
+                        color: s.color.clone(),
+                        icon: s.icon.clone(),
+                    })
+                    .collect();
+                BadgeContentType::Segments(&cased_segments)
             }
+            content => content,
+        };
+
+        let layout = self.layout_with(subject, &content);
+
+        let icon = self.icon.as_ref();
+
+        let color = self.scaled_color(&content);
+        let color = color.as_ref();
+
+        let outline = self.outline_text.then(|| self.font_set());
+
+        let icon_color = match self.style {
+            // Social style draws icons dark unless a color was set explicitly
+            Style::Social if self.icon_color == Color::white() => Cow::Owned(Color::gray_dark()),
+            _ => Cow::Borrowed(&self.icon_color),
+        };
+
+        let ctx = TemplateContext {
+            layout,
+            icon,
+            icon_color: &icon_color,
+            color,
+            content,
+            chart: self.chart,
+            chart_range: self.chart_range,
+            subject,
+            id_prefix,
+            a11y_label,
+            font_family: self.font_set().css_family(),
+            outline,
+        };
+        match self.style {
+            Style::Classic => ClassicTemplate(ctx).render_once(),
+            Style::Flat => FlatTemplate(ctx).render_once(),
+            Style::Plastic => PlasticTemplate(ctx).render_once(),
+            Style::ForTheBadge => ForTheBadgeTemplate(ctx).render_once(),
+            Style::Social => SocialTemplate(ctx).render_once(),
         }
+        .unwrap()
+    }
+}
+
+/// `content` with its text replaced by `text`.
+fn with_text<'c>(content: BadgeContentType<'c>, text: Option<&'c str>) -> BadgeContentType<'c> {
+    match (content, text) {
+        (BadgeContentType::Text(_), Some(t)) => BadgeContentType::Text(t),
+        (content, _) => content,
+    }
+}
+
+/// Longest prefix of `s` that fits in `max_width` with an ellipsis appended,
+/// or `s` itself when it fits.
+fn ellipsize(s: Cow<str>, max_width: usize, text_width: impl Fn(&str) -> usize) -> Cow<str> {
+    if text_width(&s) <= max_width {
+        return s;
+    }
+    let s = s.trim();
+    let ends: Vec<usize> = s.char_indices().map(|(i, _)| i).collect();
+    let shortened = |end: usize| format!("{}…", s[..end].trim_end());
+    let count = ends.partition_point(|&end| text_width(&shortened(end)) <= max_width);
+    Cow::Owned(shortened(ends[count.saturating_sub(1)]))
+}
+
+/// Accessible label, e.g. `build: linux | passing`.
+fn a11y_label(subject: Option<&str>, content: &BadgeContentType<'_>) -> String {
+    let mut parts: Vec<Cow<str>> = Vec::new();
+    match content {
+        BadgeContentType::Segments(segments) => {
+            parts.extend(segments.iter().map(|s| s.text.into()))
+        }
+        _ => parts.extend(content_text(content)),
+    }
+    match subject {
+        Some(s) if !parts.is_empty() => format!("{}: {}", s, parts.join(" | ")),
+        Some(s) => s.to_string(),
+        None => parts.join(" | "),
+    }
+}
+
+/// Longest numeric prefix of `s`, e.g. `12.5` for `12.5 MB`.
+fn leading_number(s: &str) -> Option<f32> {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(s.len());
+    (1..=end).rev().find_map(|i| s[..i].parse::<f32>().ok())
+}
+
+/// Text drawn over the content area, if any.
+fn content_text<'b>(content: &BadgeContentType<'b>) -> Option<Cow<'b, str>> {
+    match content {
+        BadgeContentType::Text(c) => Some((*c).into()),
+        BadgeContentType::Progress(p) => Some(format!("{}%", (p * 10.0).round() / 10.0).into()),
+        _ => None,
+    }
+}
+
+#[inline]
+fn segments<'b>(content: &BadgeContentType<'b>) -> &'b [Segment<'b>] {
+    match content {
+        BadgeContentType::Segments(segments) => segments,
+        _ => &[],
     }
 }
 
+/// Icon symbols to put in `<defs>`, each id once and prefixed with `id_prefix`.
+fn symbols<'b>(
+    icon: Option<&'b Icon<'b>>,
+    content: &BadgeContentType<'b>,
+    id_prefix: &str,
+) -> Vec<Cow<'b, str>> {
+    let segment_icons = segments(content).iter().filter_map(|s| s.icon.as_ref());
+    // Raster images are drawn inline, so only svg icons need a symbol
+    let icons = icon.into_iter().chain(segment_icons);
+    let mut names: Vec<&str> = Vec::new();
+    let mut symbols = Vec::new();
+    for i in icons.filter(|i| i.image().is_none()) {
+        if !names.contains(&i.name()) {
+            names.push(i.name());
+            symbols.push(match id_prefix {
+                "" => Cow::Borrowed(i.symbol()),
+                p => {
+                    // Symbols are inserted raw, so the prefix is escaped here
+                    let mut scoped = String::from("id=\"");
+                    escape_to_string(p, &mut scoped);
+                    let id = format!("id=\"{}\"", i.name());
+                    scoped.push_str(&id[4..]);
+                    Cow::Owned(i.symbol().replacen(&id, &scoped, 1))
+                }
+            });
+        }
+    }
+    symbols
+}
+
+// `[A-Za-z][A-Za-z0-9_-]*`, safe in ids, url(#..) references and css selectors
+pub(crate) fn valid_id_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// 32 bit FNV-1a, stable across builds so hashed ids can be cached
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |h, &b| {
+        (h ^ b as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
 impl<'a, T: BadgeType<'a>> fmt::Display for Badge<'a, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.render())
@@ -378,10 +971,32 @@ impl<'a, T: BadgeType<'a>> fmt::Display for Badge<'a, T> {
 
 #[cfg(test)]
 mod tests {
-    use super::{style::Style, Badge, Color, Size};
+    use super::{font::DEFAULT_FONTS, TextWidth};
+    use super::{leading_number, style::Style, Badge, Chart, Color, ColorScale, Segment, Size};
     use crate::Icon;
     use scraper::{Html, Selector};
-    use std::convert::TryFrom;
+
+    const STYLES: [Style; 5] = [
+        Style::Classic,
+        Style::Flat,
+        Style::Social,
+        Style::ForTheBadge,
+        Style::Plastic,
+    ];
+
+    // Text as drawn, ForTheBadge uppercases it
+    fn drawn(style: Style, s: &str) -> String {
+        match style {
+            Style::ForTheBadge => s.to_uppercase(),
+            _ => s.to_string(),
+        }
+    }
+
+    // Icon that doesn't depend on the built-in icon packs
+    fn square_icon(name: &str) -> Icon<'_> {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 8 8"><rect width="8" height="8"/></svg>"#;
+        Icon::from_svg(name, svg).unwrap()
+    }
 
     #[test]
     fn default_badge_has_classic_style() {
@@ -498,7 +1113,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "static_icons")]
+    #[cfg(feature = "icons-simple")]
     fn badge_with_icon() {
         let mut badge = Badge::new();
         badge
@@ -515,7 +1130,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "static_icons")]
+    #[cfg(feature = "icons-simple")]
     fn badge_with_icon_only() {
         let mut badge = Badge::new();
         badge.icon(Icon::try_from("git").unwrap());
@@ -530,7 +1145,53 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "static_icons")]
+    fn badge_with_svg_icon() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 8 8"><rect width="8" height="8"/></svg>"#;
+        let mut badge = Badge::new();
+        badge
+            .subject("with icon")
+            .icon(Icon::from_svg("square", svg).unwrap())
+            .id_prefix("b1-")
+            .unwrap();
+
+        let doc = Html::parse_fragment(&badge.to_string());
+        let icon_sel = Selector::parse("symbol").unwrap();
+        let icon_symbol = doc.select(&icon_sel).next().unwrap();
+        assert_eq!(icon_symbol.value().attr("id"), Some("b1-square"));
+        assert_eq!(icon_symbol.value().attr("viewBox"), Some("0 0 8 8"));
+    }
+
+    #[test]
+    fn badge_with_image_icon() {
+        let png = b"\x89PNG\r\n\x1a\n";
+        let icon = Icon::from_image("logo", png).unwrap();
+        let segments = [Segment {
+            text: "a",
+            color: None,
+            icon: Some(icon.clone()),
+        }];
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("with logo").icon(icon.clone()).style(style);
+            let badge = badge.segments(&segments);
+            let layout = badge.layout();
+
+            let doc = Html::parse_fragment(&badge.to_string());
+            let symbol_sel = Selector::parse("symbol, use").unwrap();
+            assert!(doc.select(&symbol_sel).next().is_none());
+            let images: Vec<_> = doc.select(&Selector::parse("image").unwrap()).collect();
+            assert_eq!(images.len(), 2, "{}", style);
+            let image = images[0].value();
+            let href = image.attrs().find(|(k, _)| k.ends_with("href"));
+            assert_eq!(href.map(|(_, v)| v), icon.image());
+            let rect = layout.icon.unwrap();
+            assert_eq!(image.attr("width"), Some(rect.width.to_string().as_str()));
+            assert_eq!(image.attr("x"), Some(rect.x.to_string().as_str()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "icons-simple")]
     fn social_default_icon_color_is_gray_dark() {
         let mut badge = Badge::new();
         badge
@@ -546,7 +1207,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "static_icons")]
+    #[cfg(feature = "icons-simple")]
     fn social_respects_explicit_icon_color() {
         let mut badge = Badge::new();
         badge
@@ -580,6 +1241,707 @@ mod tests {
         assert_eq!(svg.value().attr("height"), Some("40"));
     }
 
+    #[test]
+    fn custom_size_scales_badge() {
+        let layout = |size| {
+            let mut badge = Badge::new();
+            badge.subject("scaled").size(size);
+            badge.icon(square_icon("git"));
+            badge.text("badge").layout()
+        };
+        let small = layout(Size::Small);
+        assert_eq!(layout(Size::Custom(20)).width, small.width);
+
+        let big = layout(Size::Custom(80));
+        assert_eq!(big.height, 80);
+        assert_eq!(big.font_size, small.font_size * 4.);
+        assert_eq!(big.rx, small.rx * 4);
+        let icon = big.icon.unwrap();
+        assert_eq!(icon.width, small.icon.unwrap().width * 4);
+        assert!(big.width.abs_diff(small.width * 4) <= 8);
+
+        let mut badge = Badge::new();
+        badge.size(Size::Custom(16));
+        let data = badge.data(&[1., 2., 3.]).layout();
+        assert_eq!((data.height, data.content.width), (16, 16 * 5));
+
+        for (size, clamped) in [(0, 8), (1000, 512)] {
+            for style in STYLES {
+                let svg = |size| {
+                    let mut badge = Badge::new();
+                    badge.subject("s").style(style).size(Size::Custom(size));
+                    badge.text("t").to_string()
+                };
+                assert_eq!(svg(size), svg(clamped));
+            }
+        }
+    }
+
+    #[test]
+    fn layout_matches_rendered_rects() {
+        let mut badge = Badge::new();
+        badge.subject("layout");
+        let badge = badge.text("text");
+        let layout = badge.layout();
+
+        let doc = Html::parse_fragment(&badge.to_string());
+        let svg_sel = Selector::parse("svg").unwrap();
+        let svg = doc.select(&svg_sel).next().unwrap();
+        assert_eq!(
+            svg.value().attr("width"),
+            Some(layout.width.to_string().as_str())
+        );
+
+        let content_sel = Selector::parse("g#bg > rect#content").unwrap();
+        let content = doc.select(&content_sel).next().unwrap();
+        assert_eq!(
+            content.value().attr("x"),
+            Some(layout.content.x.to_string().as_str())
+        );
+        assert_eq!(
+            content.value().attr("width"),
+            Some(layout.content.width.to_string().as_str())
+        );
+
+        let text_sel = Selector::parse("g#text > text:last-child").unwrap();
+        let text = doc.select(&text_sel).next().unwrap();
+        let anchor = layout.content_text.unwrap();
+        assert_eq!(text.value().attr("x"), Some(anchor.x.to_string().as_str()));
+        assert!(layout.content.contains(anchor.x, anchor.y));
+        assert!(layout.chart.is_none());
+        assert!(layout.icon.is_none());
+    }
+
+    #[test]
+    fn social_layout_has_notch_gap() {
+        let mut badge = Badge::new();
+        badge.subject("layout").style(Style::Social);
+        let layout = badge.text("text").layout();
+        assert_eq!(layout.content.x, layout.subject.width + 6);
+        assert_eq!(layout.width, layout.content.x + layout.content.width + 1);
+    }
+
+    #[test]
+    fn data_layout_has_chart() {
+        let mut badge = Badge::new();
+        badge.subject("layout").size(Size::Medium);
+        let layout = badge.data(&[1., 2.]).layout();
+        let chart = layout.chart.unwrap();
+        assert_eq!(chart.x, layout.subject.width);
+        assert_eq!((chart.width, chart.height), (150, 30));
+        assert!(layout.content_text.is_none());
+    }
+
+    #[test]
+    fn badge_with_segments() {
+        let segments = [
+            Segment::new("linux").color(Color::gray_dark()),
+            Segment::new("passing").color(Color::black()),
+            Segment::new("2m31s"),
+        ];
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("build").style(style);
+            let badge = badge.segments(&segments);
+            let layout = badge.layout();
+
+            let doc = Html::parse_fragment(&badge.to_string());
+            let text_sel = Selector::parse("g#text > text").unwrap();
+            let texts: Vec<String> = doc
+                .select(&text_sel)
+                .map(|t| t.text().collect::<String>().trim().to_string())
+                .collect();
+            let expected: Vec<_> = ["build", "linux", "passing", "2m31s"]
+                .iter()
+                .map(|t| drawn(style, t))
+                .collect();
+            assert_eq!(texts, expected);
+
+            let svg_sel = Selector::parse("svg").unwrap();
+            let svg = doc.select(&svg_sel).next().unwrap();
+            assert_eq!(
+                svg.value().attr("aria-label"),
+                Some("build: linux | passing | 2m31s")
+            );
+
+            assert_eq!(layout.segments.len(), 3);
+            assert_eq!(layout.segments[0].rect.x, layout.content.x);
+            for pair in layout.segments.windows(2) {
+                assert_eq!(pair[0].rect.x + pair[0].rect.width, pair[1].rect.x);
+            }
+            let total: usize = layout.segments.iter().map(|s| s.rect.width).sum();
+            assert_eq!(total, layout.content.width);
+        }
+    }
+
+    #[test]
+    fn segments_use_own_colors() {
+        let segments = [Segment::new("a").color(Color::black()), Segment::new("b")];
+        let mut badge = Badge::new();
+        badge.subject("colors").color(Color::gray());
+        let doc = Html::parse_fragment(&badge.segments(&segments).to_string());
+        let rect_sel = Selector::parse("g#bg > rect:not([id])").unwrap();
+        let fills: Vec<&str> = doc
+            .select(&rect_sel)
+            .filter_map(|r| r.value().attr("fill"))
+            .filter(|f| !f.starts_with("url"))
+            .collect();
+        assert_eq!(fills, vec![Color::black().as_ref(), Color::gray().as_ref()]);
+    }
+
+    #[test]
+    #[cfg(feature = "icons-simple")]
+    fn segment_icons_are_defined_once() {
+        let segments = [
+            Segment::new("a").icon(Icon::try_from("git").unwrap()),
+            Segment::new("b").icon(Icon::try_from("git").unwrap()),
+        ];
+        let mut badge = Badge::new();
+        badge.subject("icons").icon(Icon::try_from("git").unwrap());
+        let badge = badge.segments(&segments);
+        let layout = badge.layout();
+        assert!(layout.segments.iter().all(|s| s.icon.is_some()));
+
+        let doc = Html::parse_fragment(&badge.to_string());
+        let symbol_sel = Selector::parse("symbol").unwrap();
+        assert_eq!(doc.select(&symbol_sel).count(), 1);
+        let use_sel = Selector::parse("use").unwrap();
+        assert_eq!(doc.select(&use_sel).count(), 3);
+    }
+
+    #[test]
+    fn badge_chart_kinds() {
+        let path_sel = Selector::parse("g#text > path").unwrap();
+        for (chart, paths) in [(Chart::Line, 2), (Chart::Area, 1), (Chart::Bars, 1)] {
+            let mut badge = Badge::new();
+            badge.subject("chart").chart(chart);
+            let doc = Html::parse_fragment(&badge.data(&[3., 0., -2.]).to_string());
+            let els: Vec<_> = doc.select(&path_sel).collect();
+            assert_eq!(els.len(), paths, "chart = {}", chart);
+            let fill = els.last().unwrap().value().attr("fill");
+            assert_eq!(fill, Some(Color::blue().as_ref()), "chart = {}", chart);
+        }
+    }
+
+    #[test]
+    fn bars_have_one_rect_per_value() {
+        let mut badge = Badge::new();
+        badge.chart(Chart::Bars);
+        let doc = Html::parse_fragment(&badge.data(&[1., 2., 0., 4.]).to_string());
+        let path_sel = Selector::parse("g#text > path").unwrap();
+        let d = doc
+            .select(&path_sel)
+            .next()
+            .unwrap()
+            .value()
+            .attr("d")
+            .unwrap();
+        assert_eq!(d.matches('M').count(), 4);
+        assert!(!d.contains("NaN") && !d.contains("inf"));
+    }
+
+    #[test]
+    fn chart_y_range_override() {
+        let mut badge = Badge::new();
+        badge.y_min(0.).y_max(8.);
+        let doc = Html::parse_fragment(&badge.data(&[2., 4.]).to_string());
+        let path_sel = Selector::parse("g#text > path").unwrap();
+        let d = doc
+            .select(&path_sel)
+            .next()
+            .unwrap()
+            .value()
+            .attr("d")
+            .unwrap();
+        assert_eq!(d, "M0 15L100 10");
+    }
+
+    #[test]
+    fn progress_fills_content() {
+        let mut badge = Badge::new();
+        badge.subject("coverage");
+        let layout = badge.progress(87.5).layout();
+        let bar = layout.progress.unwrap();
+        assert_eq!(bar.x, layout.content.x);
+        assert_eq!(layout.content.width, 100);
+        assert_eq!(bar.width, 88);
+        assert!(layout.content_text.is_some());
+
+        for (value, width) in [(150., 100), (-5., 0), (f32::NAN, 0)] {
+            let bar = Badge::new().progress(value).layout().progress.unwrap();
+            assert_eq!(bar.width, width, "value = {}", value);
+        }
+    }
+
+    #[test]
+    fn progress_renders_in_every_style() {
+        let progress_sel = Selector::parse("rect#progress").unwrap();
+        let text_sel = Selector::parse("g#text > text").unwrap();
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("coverage").style(style);
+            let badge = badge.progress(87.5);
+            let content = badge.layout().content;
+            let width = (content.width as f32 * 0.875).round().to_string();
+            let svg = badge.to_string();
+            let doc = Html::parse_fragment(&svg);
+            let bar = doc.select(&progress_sel).next().unwrap().value();
+            assert_eq!(bar.attr("width"), Some(&*width), "style = {}", style);
+            let texts: Vec<_> = doc.select(&text_sel).map(|t| t.inner_html()).collect();
+            assert_eq!(texts.len(), 2, "style = {}", style);
+            assert_eq!(texts[1].trim(), "87.5%", "style = {}", style);
+            assert!(svg.contains("aria-label=\"coverage: 87.5%\""));
+        }
+    }
+
+    #[test]
+    fn progress_scale_colors() {
+        let red: Color = "red".parse().unwrap();
+        let (dark, black) = (Color::gray_dark(), Color::black());
+        let scale = ColorScale::steps(vec![(50., black.clone()), (80., dark.clone())]).unwrap();
+        let progress_sel = Selector::parse("rect#progress").unwrap();
+        for (value, color) in [(20., &red), (50., &black), (100., &dark)] {
+            let mut badge = Badge::new();
+            badge.color(red.clone()).color_scale(&scale);
+            let doc = Html::parse_fragment(&badge.progress(value).to_string());
+            let bar = doc.select(&progress_sel).next().unwrap();
+            let fill = bar.value().attr("fill");
+            assert_eq!(fill, Some(color.as_ref()), "value = {}", value);
+        }
+    }
+
+    #[test]
+    fn color_scale_picks_content_color() {
+        let scale: ColorScale = "0:red,50:lime".parse().unwrap();
+        let (red, lime) = ("rgb(255, 0, 0)", "rgb(0, 255, 0)");
+        let content_sel = Selector::parse("rect#content").unwrap();
+        let fill = |svg: String| {
+            let doc = Html::parse_fragment(&svg);
+            let rect = doc.select(&content_sel).next().unwrap();
+            rect.value().attr("fill").map(String::from)
+        };
+
+        for (text, color) in [
+            ("12.5s", red),
+            ("87%", lime),
+            ("-3", Color::blue().as_ref()),
+        ] {
+            let mut badge = Badge::new();
+            badge.color_scale(&scale);
+            assert_eq!(fill(badge.text(text).to_string()).as_deref(), Some(color));
+        }
+
+        // Data badges use the last finite point, the chart is drawn in that color
+        let mut badge = Badge::new();
+        badge.color_scale(&scale);
+        let svg = badge.data(&[80., 10., f32::NAN]).to_string();
+        let doc = Html::parse_fragment(&svg);
+        let path_sel = Selector::parse("g#text > path").unwrap();
+        let path = doc.select(&path_sel).next().unwrap();
+        assert_eq!(path.value().attr("stroke"), Some(red));
+    }
+
+    #[test]
+    fn leading_numbers() {
+        assert_eq!(leading_number("87.5%"), Some(87.5));
+        assert_eq!(leading_number(" -3 errors"), Some(-3.));
+        assert_eq!(leading_number("1.2.3"), Some(1.2));
+        assert_eq!(leading_number("1e3ms"), Some(1000.));
+        assert_eq!(leading_number("v1"), None);
+        assert_eq!(leading_number(""), None);
+    }
+
+    const HOSTILE: &[&str] = &[
+        "a<b & c",
+        "</text><script>alert(1)</script>",
+        "\"onload=\"alert(1)",
+        "'quoted' \"double\"",
+        "]]><![CDATA[",
+        "&amp; &#x3c; &lt;",
+    ];
+
+    #[test]
+    fn hostile_text_renders_valid_xml() {
+        for style in STYLES {
+            for s in HOSTILE {
+                let mut badge = Badge::new();
+                badge.subject(s).style(style);
+                let svg = badge.text(s).to_string();
+                let doc = roxmltree::Document::parse(&svg)
+                    .unwrap_or_else(|e| panic!("style = {}, input = {:?}: {}", style, s, e));
+
+                assert!(!doc.descendants().any(|n| n.has_tag_name("script")));
+                let root = doc.root_element();
+                assert!(root.attributes().all(|a| !a.name().starts_with("on")));
+                assert_eq!(
+                    root.attribute("aria-label"),
+                    Some(format!("{}: {}", s, s).as_str())
+                );
+
+                let texts: Vec<String> = doc
+                    .descendants()
+                    .filter(|n| n.has_tag_name("text"))
+                    .map(|n| n.text().unwrap_or_default().trim().to_string())
+                    .collect();
+                assert_eq!(texts, vec![drawn(style, s), drawn(style, s)]);
+            }
+        }
+    }
+
+    #[test]
+    fn hostile_icon_name_renders_valid_xml() {
+        for style in STYLES {
+            for s in HOSTILE {
+                let mut badge = Badge::new();
+                badge
+                    .subject("icon")
+                    .style(style)
+                    .icon(Icon::new(s, "<symbol viewBox=\"0 0 1 1\"/>"));
+                let svg = badge.text("text").to_string();
+                let doc = roxmltree::Document::parse(&svg)
+                    .unwrap_or_else(|e| panic!("style = {}, input = {:?}: {}", style, s, e));
+                let href = doc
+                    .descendants()
+                    .find(|n| n.has_tag_name("use"))
+                    .and_then(|n| n.attribute(("http://www.w3.org/1999/xlink", "href")));
+                assert_eq!(href, Some(format!("#{}", s).as_str()));
+            }
+        }
+    }
+
+    // Every id starts with `prefix` and every reference points at one of them
+    fn assert_scoped_ids(svg: &str, prefix: &str) {
+        let doc = roxmltree::Document::parse(svg).unwrap();
+        let ids: Vec<&str> = doc
+            .descendants()
+            .filter_map(|n| n.attribute("id"))
+            .collect();
+        assert!(!ids.is_empty());
+        assert!(ids.iter().all(|id| id.starts_with(prefix)), "{:?}", ids);
+
+        let xlink = ("http://www.w3.org/1999/xlink", "href");
+        for node in doc.descendants() {
+            let refs = ["fill", "mask", "filter"]
+                .iter()
+                .filter_map(|a| node.attribute(*a))
+                .filter_map(|v| v.strip_prefix("url(#")?.strip_suffix(')'))
+                .chain(node.attribute(xlink).and_then(|v| v.strip_prefix('#')));
+            for r in refs {
+                assert!(ids.contains(&r), "dangling reference {:?}", r);
+            }
+        }
+    }
+
+    #[test]
+    fn id_prefix_scopes_every_id() {
+        let segments = [Segment::new("seg").icon(square_icon("github"))];
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge
+                .subject("ids")
+                .style(style)
+                .icon(square_icon("git"))
+                .id_prefix("b1-")
+                .unwrap();
+            let svg = badge.segments(&segments).to_string();
+            assert_scoped_ids(&svg, "b1-");
+            if style == Style::Social {
+                assert!(svg.contains("a:hover #b1-llink"));
+            }
+        }
+    }
+
+    #[test]
+    fn hashed_ids_differ_per_badge() {
+        let prefix = |text: &str| {
+            let mut badge = Badge::new();
+            badge.subject("ids").hashed_ids();
+            let svg = badge.text(text).to_string();
+            let doc = roxmltree::Document::parse(&svg).unwrap();
+            let bg = doc.descendants().find(|n| n.has_tag_name("g")).unwrap();
+            let id = bg.attribute("id").unwrap();
+            let prefix = id.trim_end_matches("bg").to_string();
+            assert_scoped_ids(&svg, &prefix);
+            prefix
+        };
+        assert_eq!(prefix("one"), prefix("one"));
+        assert_ne!(prefix("one"), prefix("two"));
+        assert!(prefix("one").starts_with('b'));
+    }
+
+    #[test]
+    fn hostile_id_prefix_is_rejected() {
+        let css = [
+            "x{}*{fill:url(https://evil.example/t)} ",
+            "a:hover",
+            "1a",
+            "a b",
+            "",
+        ];
+        for s in HOSTILE.iter().chain(&css) {
+            let mut badge = Badge::new();
+            assert!(badge.id_prefix(s).is_err(), "input = {:?}", s);
+        }
+        for s in ["b", "b1-", "Badge_2"] {
+            let mut badge = Badge::new();
+            badge.icon(square_icon("git")).id_prefix(s).unwrap();
+            let svg = badge.text("text").to_string();
+            assert_scoped_ids(&svg, s);
+        }
+    }
+
+    #[test]
+    fn outline_text_replaces_text_elements() {
+        let segments = [Segment::new("linux"), Segment::new("passing")];
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("build").style(style);
+            let plain = badge.text("passing").to_string();
+            let mut badge = Badge::new();
+            badge.subject("build").style(style).outline_text();
+            let outlined = badge.text("passing").to_string();
+            let mut badge = Badge::new();
+            badge.subject("build").style(style).outline_text();
+            let outlined_segments = badge.segments(&segments).to_string();
+
+            for svg in [&outlined, &outlined_segments] {
+                let doc = roxmltree::Document::parse(svg).unwrap();
+                assert!(!doc.descendants().any(|n| n.has_tag_name("text")));
+                let text = doc
+                    .descendants()
+                    .find(|n| n.attribute("id") == Some("text"));
+                let paths = text.unwrap().children().filter(|n| n.has_tag_name("path"));
+                assert!(paths.count() >= 2);
+            }
+
+            // Same size and label as the `<text>` version, but outlines are
+            // traced from the regular face, narrower than bold ForTheBadge text
+            let root = |svg: &str| {
+                let doc = roxmltree::Document::parse(svg).unwrap();
+                let root = doc.root_element();
+                ["width", "height", "aria-label"].map(|a| root.attribute(a).map(str::to_owned))
+            };
+            let (plain, outlined) = (root(&plain), root(&outlined));
+            assert_eq!(plain[1..], outlined[1..]);
+            if style == Style::ForTheBadge {
+                let width = |r: &[Option<String>]| r[0].as_deref().unwrap().parse::<usize>();
+                assert!(width(&outlined).unwrap() < width(&plain).unwrap());
+            } else {
+                assert_eq!(plain[0], outlined[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn max_width_shortens_text() {
+        let long = "Merge branch 'feature/very-long-branch-name' into main";
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("commit").style(style).max_width(150);
+            let badge = badge.text(long);
+            assert!(badge.layout().width <= 150);
+
+            let svg = badge.to_string();
+            let doc = roxmltree::Document::parse(&svg).unwrap();
+            let texts: Vec<_> = doc
+                .descendants()
+                .filter(|n| n.has_tag_name("text"))
+                .filter_map(|n| n.text())
+                .map(str::trim)
+                .collect();
+            // The short subject is kept, the text is cut
+            assert_eq!(texts[0], drawn(style, "commit"));
+            assert!(texts[1].starts_with(&drawn(style, "Merge")) && texts[1].ends_with('…'));
+
+            let label = format!("commit: {}", long);
+            let root = doc.root_element();
+            assert_eq!(root.attribute("aria-label"), Some(label.as_str()));
+            let title = doc.descendants().find(|n| n.has_tag_name("title"));
+            assert_eq!(title.and_then(|t| t.text()), Some(label.as_str()));
+        }
+    }
+
+    #[test]
+    fn max_width_splits_space_between_long_texts() {
+        let long = "a rather long piece of badge text";
+        let mut badge = Badge::new();
+        badge.subject(long).max_width(200);
+        let badge = badge.text(long);
+        let layout = badge.layout();
+        assert!(layout.width <= 200);
+        assert!(layout.subject.width.abs_diff(layout.content.width) <= 1);
+
+        // Nothing left for text still yields an ellipsis
+        let mut badge = Badge::new();
+        badge.subject(long).max_width(0);
+        let svg = badge.text(long).to_string();
+        assert_eq!(svg.matches('…').count(), 2);
+    }
+
+    #[test]
+    fn max_width_keeps_short_text() {
+        let mut badge = Badge::new();
+        badge.subject("build");
+        let plain = badge.text("passing").to_string();
+        let mut badge = Badge::new();
+        badge.subject("build").max_width(500);
+        assert_eq!(badge.text("passing").to_string(), plain);
+    }
+
+    #[test]
+    fn rtl_text_sets_direction() {
+        let text_sel = Selector::parse("text").unwrap();
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("בנייה").style(style);
+            let svg = badge.text("build עובר").to_string();
+            let doc = Html::parse_fragment(&svg);
+            let dirs: Vec<_> = doc
+                .select(&text_sel)
+                .map(|t| t.value().attr("direction"))
+                .collect();
+            assert_eq!(dirs, vec![Some("rtl"), None]);
+
+            let segments = [Segment::new("linux"), Segment::new("לינוקס")];
+            let mut badge = Badge::new();
+            badge.style(style);
+            let svg = badge.segments(&segments).to_string();
+            let doc = Html::parse_fragment(&svg);
+            let dirs: Vec<_> = doc
+                .select(&text_sel)
+                .map(|t| t.value().attr("direction"))
+                .collect();
+            assert_eq!(dirs, vec![None, Some("rtl")]);
+        }
+    }
+
+    #[test]
+    fn mirrored_segments_run_right_to_left() {
+        let segments = [
+            Segment::new("one"),
+            Segment::new("two"),
+            Segment::new("three"),
+        ];
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 8 8"><rect width="8" height="8"/></svg>"#;
+        let icon = Icon::from_svg("square", svg).unwrap();
+        let text_sel = Selector::parse("text").unwrap();
+        for style in STYLES {
+            let mut badge = Badge::new();
+            badge.subject("build").icon(icon.clone()).style(style);
+            let layout = badge.segments(&segments).layout();
+            let mut badge = Badge::new();
+            badge
+                .subject("build")
+                .icon(icon.clone())
+                .style(style)
+                .mirror_segments();
+            let badge = badge.segments(&segments);
+            let mirrored = badge.layout();
+
+            assert_eq!(mirrored.width, layout.width);
+            let end = layout.content.x + layout.content.width;
+            for (seg, mirror) in layout.segments.iter().zip(&mirrored.segments) {
+                assert_eq!(mirror.rect.width, seg.rect.width);
+                assert_eq!(mirror.rect.x, end - seg.rect.x - seg.rect.width);
+            }
+            assert_eq!(mirrored.content.x, 0);
+            assert_eq!(mirrored.segments[2].rect.x, 0);
+
+            // Subject and its icon move to the right edge
+            assert_eq!(mirrored.subject.x + mirrored.subject.width, end);
+            assert_eq!(mirrored.subject.width, layout.subject.width);
+            let icon_rect = mirrored.icon.unwrap();
+            assert!(icon_rect.x >= mirrored.subject.x);
+            assert_eq!(icon_rect.x + icon_rect.width, end - layout.icon.unwrap().x);
+
+            // Text is drawn in order at the mirrored positions
+            let doc = Html::parse_fragment(&badge.to_string());
+            let xs: Vec<(String, usize)> = doc
+                .select(&text_sel)
+                .map(|t| {
+                    let x = t.value().attr("x").unwrap().parse().unwrap();
+                    (t.text().collect::<String>().trim().to_string(), x)
+                })
+                .collect();
+            assert!(xs[1].0.eq_ignore_ascii_case("one"));
+            assert!(xs.windows(2).all(|w| w[0].1 > w[1].1), "{:?}", style);
+
+            if style != Style::Social {
+                let subject_sel = Selector::parse("rect#subject").unwrap();
+                let subject = doc.select(&subject_sel).next().unwrap();
+                let x = mirrored.subject.x.to_string();
+                assert_eq!(subject.value().attr("x"), Some(x.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn for_the_badge_style() {
+        let mut badge = Badge::new();
+        badge.subject("build");
+        let flat = badge.text("passing").layout();
+        let mut badge = Badge::new();
+        badge.subject("build").style(Style::ForTheBadge);
+        let badge = badge.text("passing");
+        let layout = badge.layout();
+        assert_eq!((layout.height, layout.rx), (28, 0));
+        assert!(layout.letter_spacing > 0.);
+        assert!(layout.font_size < flat.font_size);
+
+        let doc = Html::parse_fragment(&badge.to_string());
+        let text_sel = Selector::parse("text").unwrap();
+        let texts: Vec<String> = doc
+            .select(&text_sel)
+            .map(|t| t.text().collect::<String>().trim().to_string())
+            .collect();
+        assert_eq!(texts, vec!["BUILD", "PASSING"]);
+        let g_sel = Selector::parse("g[letter-spacing]").unwrap();
+        let g = doc.select(&g_sel).next().unwrap();
+        assert_eq!(g.value().attr("font-weight"), Some("bold"));
+        let svg = doc.select(&Selector::parse("svg").unwrap()).next().unwrap();
+        assert_eq!(svg.value().attr("aria-label"), Some("build: passing"));
+
+        // Spacing and uppercase glyphs take more room than the plain text
+        let plain = "passing".text_width(layout.font_size, &DEFAULT_FONTS);
+        let upper = "PASSING".text_width(layout.font_size, &DEFAULT_FONTS);
+        assert!(upper > plain);
+        assert!(layout.content.width > upper + layout.height / 2);
+
+        for s in ["for-the-badge", "ForTheBadge", "b"] {
+            assert_eq!(s.parse::<Style>().unwrap(), Style::ForTheBadge);
+        }
+        let round_trip: Style = Style::ForTheBadge.to_string().parse().unwrap();
+        assert_eq!(round_trip, Style::ForTheBadge);
+    }
+
+    #[test]
+    fn plastic_style() {
+        let mut badge = Badge::new();
+        badge.subject("build").style(Style::Plastic);
+        let badge = badge.text("passing");
+        let layout = badge.layout();
+        let mut classic = Badge::new();
+        classic.subject("build");
+        let classic = classic.text("passing").layout();
+        assert_eq!(layout.width, classic.width);
+        assert_eq!(layout.height, classic.height);
+        assert!(layout.rx > classic.rx);
+
+        let doc = Html::parse_fragment(&badge.to_string());
+        let stop_sel = Selector::parse("linearGradient stop").unwrap();
+        assert_eq!(doc.select(&stop_sel).count(), 4);
+        // Gloss is drawn over the fills
+        let rect_sel = Selector::parse("g > rect").unwrap();
+        let last = doc.select(&rect_sel).next_back().unwrap();
+        assert_eq!(last.value().attr("fill"), Some("url(#a)"));
+
+        for s in ["plastic", "Plastic", "p"] {
+            assert_eq!(s.parse::<Style>().unwrap(), Style::Plastic);
+        }
+        let round_trip: Style = Style::Plastic.to_string().parse().unwrap();
+        assert_eq!(round_trip, Style::Plastic);
+    }
+
     #[test]
     fn badge_with_data() {
         let mut badge = Badge::new();
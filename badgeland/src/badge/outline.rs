@@ -0,0 +1,118 @@
+use super::{bidi::visual_order, font::FontSet, BadgeLayout, Point};
+use ab_glyph::{Font, OutlineCurve, Point as GlyphPoint};
+use std::fmt::Write;
+use unicode_normalization::UnicodeNormalization;
+
+/// Svg path of `text` drawn with the glyph outlines of `fonts` at the layout
+/// font size and letter spacing, centered on `anchor` like
+/// `text-anchor="middle" dominant-baseline="middle"`.
+pub(super) fn text_path(
+    fonts: &FontSet,
+    text: &str,
+    layout: &BadgeLayout,
+    anchor: &Point,
+) -> String {
+    let (font_size, spacing) = (layout.font_size, layout.letter_spacing);
+    let text: String = text.trim().nfc().collect();
+    let (glyphs, width) = fonts.layout(&visual_order(&text));
+    let width = width * font_size + spacing * glyphs.len() as f32;
+
+    // Center the run horizontally, and vertically on half the x-height like
+    // `dominant-baseline="middle"`
+    let primary = &fonts.loaded()[0];
+    let x_height = primary
+        .font
+        .outline(primary.font.glyph_id('x'))
+        .map_or(primary.font.ascent_unscaled() / 2.0, |o| {
+            o.bounds.min.y.max(o.bounds.max.y)
+        });
+    let origin_x = anchor.x as f32 - width / 2.0;
+    let baseline = anchor.y as f32 + x_height / 2.0 * primary.scale(font_size);
+
+    let mut path = String::with_capacity(text.len() * 120);
+    for (i, (font, id, gx)) in glyphs.into_iter().enumerate() {
+        let (gx, scale) = (gx * font_size + spacing * i as f32, font.scale(font_size));
+        let Some(outline) = font.font.outline(id) else {
+            continue;
+        };
+        let map = |p: GlyphPoint| {
+            (
+                round(origin_x + gx + p.x * scale),
+                round(baseline - p.y * scale),
+            )
+        };
+
+        let mut current = None;
+        for curve in &outline.curves {
+            let (start, cmd) = match curve {
+                OutlineCurve::Line(p0, p1) => (*p0, format_points('L', &[map(*p1)])),
+                OutlineCurve::Quad(p0, p1, p2) => (*p0, format_points('Q', &[map(*p1), map(*p2)])),
+                OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                    (*p0, format_points('C', &[map(*p1), map(*p2), map(*p3)]))
+                }
+            };
+            let start = map(start);
+            if current != Some(start) {
+                if current.is_some() {
+                    path.push('Z');
+                }
+                write!(path, "M{} {}", start.0, start.1).unwrap();
+            }
+            path.push_str(&cmd);
+            current = Some(match curve {
+                OutlineCurve::Line(_, p)
+                | OutlineCurve::Quad(_, _, p)
+                | OutlineCurve::Cubic(_, _, _, p) => map(*p),
+            });
+        }
+        if current.is_some() {
+            path.push('Z');
+        }
+    }
+    path
+}
+
+#[inline]
+fn round(v: f32) -> f32 {
+    (v * 100.0).round() / 100.0
+}
+
+fn format_points(cmd: char, points: &[(f32, f32)]) -> String {
+    let mut s = String::with_capacity(points.len() * 14);
+    s.push(cmd);
+    for (i, (x, y)) in points.iter().enumerate() {
+        if i > 0 {
+            s.push(' ');
+        }
+        write!(s, "{} {}", x, y).unwrap();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::text_path;
+    use crate::badge::{font::DEFAULT_FONTS, BadgeLayout, Point};
+
+    #[test]
+    fn path_is_centered_on_anchor() {
+        let anchor = Point { x: 50, y: 10 };
+        let layout = BadgeLayout {
+            font_size: 13.,
+            ..BadgeLayout::default()
+        };
+        let path = text_path(&DEFAULT_FONTS, "npm", &layout, &anchor);
+        assert!(path.starts_with('M') && path.ends_with('Z'));
+
+        let xs: Vec<f32> = path
+            .split(|c: char| c.is_ascii_alphabetic())
+            .flat_map(|cmd| cmd.split(' ').step_by(2))
+            .filter_map(|x| x.parse().ok())
+            .collect();
+        let min = xs.iter().cloned().fold(f32::MAX, f32::min);
+        let max = xs.iter().cloned().fold(f32::MIN, f32::max);
+        assert!((min + max) / 2. - 50. < 1.5 && 50. - (min + max) / 2. < 1.5);
+
+        assert_eq!(text_path(&DEFAULT_FONTS, "  ", &layout, &anchor), "");
+    }
+}
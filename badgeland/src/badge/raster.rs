@@ -0,0 +1,194 @@
+use super::{font::FONT_DATA, Badge, BadgeType, FontSet};
+use crate::RasterError;
+use image_webp::{ColorType, WebPEncoder};
+use once_cell::sync::Lazy;
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{fontdb::Database, Options, Tree},
+};
+use std::sync::Arc;
+
+// Font database holding only the bundled Verdana, so rasterized text uses the
+// same glyphs `TextWidth` measured against, regardless of installed fonts.
+static FONT_DB: Lazy<Arc<Database>> = Lazy::new(|| {
+    let mut db = Database::new();
+    db.load_font_data(FONT_DATA.to_vec());
+    db.set_sans_serif_family("Verdana");
+    Arc::new(db)
+});
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum RasterFormat {
+    Png,
+    WebP,
+}
+
+impl<'a, T: BadgeType<'a>> Badge<'a, T> {
+    /// Render the badge svg to PNG bytes. `scale` multiplies the svg size,
+    /// so a small badge rendered with `2.0` is 40px tall.
+    pub fn render_png(&self, scale: f32) -> Result<Vec<u8>, RasterError> {
+        self.render_raster(scale, RasterFormat::Png)
+    }
+
+    /// Render the badge svg to lossless WebP bytes.
+    pub fn render_webp(&self, scale: f32) -> Result<Vec<u8>, RasterError> {
+        self.render_raster(scale, RasterFormat::WebP)
+    }
+
+    pub fn render_raster(&self, scale: f32, format: RasterFormat) -> Result<Vec<u8>, RasterError> {
+        let pixmap = self.rasterize(scale)?;
+        match format {
+            RasterFormat::Png => pixmap
+                .encode_png()
+                .map_err(|e| RasterError::Encode(e.to_string())),
+            RasterFormat::WebP => encode_webp(&pixmap),
+        }
+    }
+
+    fn rasterize(&self, scale: f32) -> Result<Pixmap, RasterError> {
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err(RasterError::Scale(scale));
+        }
+
+        let fontdb = match self.fonts {
+            Some(fonts) => Arc::new(font_db(fonts)),
+            None => FONT_DB.clone(),
+        };
+        let opt = Options {
+            fontdb,
+            ..Options::default()
+        };
+        let tree =
+            Tree::from_str(&self.render(), &opt).map_err(|e| RasterError::Svg(e.to_string()))?;
+
+        let size = tree
+            .size()
+            .to_int_size()
+            .scale_by(scale)
+            .ok_or(RasterError::Scale(scale))?;
+        let mut pixmap =
+            Pixmap::new(size.width(), size.height()).ok_or(RasterError::Scale(scale))?;
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        Ok(pixmap)
+    }
+}
+
+// Database holding exactly the fonts of `fonts`, primary font as sans-serif.
+fn font_db(fonts: &FontSet) -> Database {
+    let mut db = Database::new();
+    for font in fonts.loaded() {
+        db.load_font_data(font.font.as_slice().to_vec());
+    }
+    if let Some(family) = fonts.families().next() {
+        db.set_sans_serif_family(family);
+    }
+    db
+}
+
+fn encode_webp(pixmap: &Pixmap) -> Result<Vec<u8>, RasterError> {
+    // tiny-skia stores premultiplied alpha; encoders expect straight alpha
+    let data: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    WebPEncoder::new(&mut buf)
+        .encode(&data, pixmap.width(), pixmap.height(), ColorType::Rgba8)
+        .map_err(|e| RasterError::Encode(e.to_string()))?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RasterFormat;
+    use crate::{badge::font::FONT_DATA, Badge, FontSet, Icon, Style};
+    use resvg::tiny_skia::{Color, Pixmap};
+
+    fn png_size(png: &[u8]) -> (u32, u32) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let w = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let h = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        (w, h)
+    }
+
+    #[test]
+    fn png_matches_svg_size() {
+        for style in [Style::Classic, Style::Flat, Style::Social] {
+            let mut badge = Badge::new();
+            badge.subject("raster").style(style);
+            let badge = badge.text("png");
+
+            let svg = badge.to_string();
+            let doc = roxmltree::Document::parse(&svg).unwrap();
+            let width: u32 = doc
+                .root_element()
+                .attribute("width")
+                .unwrap()
+                .parse()
+                .unwrap();
+
+            assert_eq!(png_size(&badge.render_png(1.0).unwrap()), (width, 20));
+            assert_eq!(png_size(&badge.render_png(2.0).unwrap()), (width * 2, 40));
+        }
+    }
+
+    #[test]
+    fn webp_has_riff_header() {
+        let mut badge = Badge::new();
+        badge.subject("raster");
+        let webp = badge
+            .data(&[1., 3., 2.])
+            .render_raster(1.5, RasterFormat::WebP)
+            .unwrap();
+        assert_eq!(&webp[..4], b"RIFF");
+        assert_eq!(&webp[8..12], b"WEBP");
+    }
+
+    #[test]
+    fn invalid_scale_fails() {
+        let badge = Badge::new().text("scale");
+        assert!(badge.render_png(0.0).is_err());
+        assert!(badge.render_png(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn renders_with_custom_fonts() {
+        let mut fonts = FontSet::new();
+        fonts.add_font("Brand Sans", FONT_DATA.to_vec()).unwrap();
+        let mut badge = Badge::new();
+        badge.subject("fonts").fonts(&fonts);
+        let png = badge.text("custom").render_png(1.0).unwrap();
+        assert_eq!(png_size(&png).1, 20);
+    }
+
+    #[test]
+    fn draws_png_logo() {
+        let mut logo = Pixmap::new(4, 4).unwrap();
+        logo.fill(Color::from_rgba8(255, 0, 0, 255));
+        let png = logo.encode_png().unwrap();
+
+        let mut badge = Badge::new();
+        badge
+            .subject("logo")
+            .icon(Icon::from_image("logo", &png).unwrap());
+        let badge = badge.text("png");
+        let icon = badge.layout().icon.unwrap();
+
+        let pixmap = badge.rasterize(1.0).unwrap();
+        let (x, y) = (icon.x + icon.width / 2, icon.y + icon.height / 2);
+        let pixel = pixmap.pixel(x as u32, y as u32).unwrap();
+        assert_eq!(
+            (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()),
+            (255, 0, 0, 255)
+        );
+    }
+}
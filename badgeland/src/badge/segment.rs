@@ -0,0 +1,47 @@
+use crate::{Color, Icon};
+
+/// One part of a multi-segment badge, drawn after the subject.
+///
+/// ```
+/// use badgeland::{Badge, Color, Segment};
+///
+/// let segments = [
+///     Segment::new("linux").color(Color::gray_dark()),
+///     Segment::new("passing").color("green".parse().unwrap()),
+///     Segment::new("2m31s"),
+/// ];
+/// let mut badge = Badge::new();
+/// badge.subject("build");
+/// let svg = badge.segments(&segments).to_string();
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Segment<'a> {
+    pub(super) text: &'a str,
+    pub(super) color: Option<Color>,
+    pub(super) icon: Option<Icon<'a>>,
+}
+
+impl<'a> Segment<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Segment {
+            text,
+            color: None,
+            icon: None,
+        }
+    }
+
+    /// Background color. Defaults to the badge color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn icon(mut self, icon: Icon<'a>) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+}
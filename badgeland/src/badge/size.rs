@@ -5,12 +5,40 @@ use std::{fmt, str::FromStr};
 use serde::{de, Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, PartialEq, Copy, Clone, Default)]
-#[cfg_attr(feature = "serde_de", derive(Serialize))]
 pub enum Size {
     Large,
     Medium,
     #[default]
     Small,
+    /// Height in px. Font, padding, icon and chart scale with it. Clamped to
+    /// `CUSTOM_RANGE` when set on a badge
+    Custom(u16),
+}
+
+impl Size {
+    /// Smallest and largest accepted custom height in px
+    pub const CUSTOM_RANGE: std::ops::RangeInclusive<u16> = 8..=512;
+
+    /// Custom height moved into `CUSTOM_RANGE`
+    pub(super) fn clamped(self) -> Self {
+        match self {
+            Size::Custom(h) => {
+                let (min, max) = Size::CUSTOM_RANGE.into_inner();
+                Size::Custom(h.clamp(min, max))
+            }
+            s => s,
+        }
+    }
+
+    /// Height in px
+    pub(super) fn height(&self) -> usize {
+        match self {
+            Size::Large => 40,
+            Size::Medium => 30,
+            Size::Small => 20,
+            Size::Custom(h) => *h as usize,
+        }
+    }
 }
 
 impl fmt::Display for Size {
@@ -19,11 +47,20 @@ impl fmt::Display for Size {
             Size::Large => "Large",
             Size::Medium => "Medium",
             Size::Small => "Small",
+            Size::Custom(h) => return write!(f, "{}", h),
         };
         write!(f, "{}", s)
     }
 }
 
+// Same string form as `Deserialize` reads, custom sizes as their height
+#[cfg(feature = "serde_de")]
+impl Serialize for Size {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 #[cfg(feature = "serde_de")]
 impl<'de> Deserialize<'de> for Size {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -43,7 +80,39 @@ impl FromStr for Size {
             "large" | "l" => Ok(Size::Large),
             "medium" | "m" => Ok(Size::Medium),
             "small" | "s" => Ok(Size::Small),
-            _ => Err(SizeError {}),
+            s => s
+                .strip_suffix("px")
+                .unwrap_or(s)
+                .parse()
+                .ok()
+                .filter(|h| Size::CUSTOM_RANGE.contains(h))
+                .map(Size::Custom)
+                .ok_or(SizeError {}),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Size;
+
+    #[test]
+    fn parse_custom_height() {
+        assert_eq!("64".parse::<Size>().unwrap(), Size::Custom(64));
+        assert_eq!("16px".parse::<Size>().unwrap(), Size::Custom(16));
+        assert_eq!("m".parse::<Size>().unwrap(), Size::Medium);
+        for s in ["0", "4", "1000", "-20", "20.5", "px"] {
+            assert!(s.parse::<Size>().is_err(), "{} parsed", s);
+        }
+        let round_trip: Size = Size::Custom(48).to_string().parse().unwrap();
+        assert_eq!(round_trip, Size::Custom(48));
+    }
+
+    #[test]
+    #[cfg(feature = "serde_de")]
+    fn serde_round_trip() {
+        use serde_test::{assert_tokens, Token};
+        assert_tokens(&Size::Large, &[Token::Str("Large")]);
+        assert_tokens(&Size::Custom(48), &[Token::Str("48")]);
+    }
+}
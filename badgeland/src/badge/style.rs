@@ -11,6 +11,10 @@ pub enum Style {
     Classic,
     Flat,
     Social,
+    /// Taller, square badge with uppercase, letter-spaced bold text
+    ForTheBadge,
+    /// Classic badge with a glossy gradient and rounder corners
+    Plastic,
 }
 
 impl fmt::Display for Style {
@@ -19,6 +23,8 @@ impl fmt::Display for Style {
             Style::Classic => "Classic",
             Style::Flat => "Flat",
             Style::Social => "Social",
+            Style::ForTheBadge => "ForTheBadge",
+            Style::Plastic => "Plastic",
         };
         write!(f, "{}", s)
     }
@@ -32,7 +38,7 @@ impl<'de> Deserialize<'de> for Style {
     {
         let s = String::deserialize(deserializer)?;
 
-        Style::from_str(&s).map_err(|e| de::Error::custom(e))
+        Style::from_str(&s).map_err(de::Error::custom)
     }
 }
 
@@ -43,6 +49,8 @@ impl FromStr for Style {
             "classic" | "c" => Ok(Style::Classic),
             "flat" | "f" => Ok(Style::Flat),
             "social" | "s" => Ok(Style::Social),
+            "for-the-badge" | "for_the_badge" | "forthebadge" | "b" => Ok(Style::ForTheBadge),
+            "plastic" | "p" => Ok(Style::Plastic),
             _ => Err(Self::Err {}),
         }
     }
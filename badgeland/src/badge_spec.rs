@@ -0,0 +1,944 @@
+use super::{
+    badge::valid_id_prefix, Badge, BadgeColor, BadgeData, Chart, Color, ColorScale, Icon,
+    IconRegistry, IdPrefixError, Segment, Size, SpecError, Style,
+};
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde_de")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde_de")]
+use std::ops::Not;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum BadgeContent {
+    #[default]
+    None,
+    Text(String),
+    Data(BadgeData),
+    Segments(Vec<SegmentSpec>),
+    /// Percentage, see [`Badge::progress`]
+    Progress(f32),
+}
+
+impl FromStr for BadgeContent {
+    type Err = std::convert::Infallible;
+
+    /// Comma separated numbers with more than one value become `Data`,
+    /// everything else is `Text`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<BadgeData>() {
+            Ok(d) if d.0.len() > 1 => BadgeContent::Data(d),
+            _ => BadgeContent::Text(s.to_string()),
+        })
+    }
+}
+
+/// Owned [`Segment`] of a [`BadgeContent::Segments`] badge.
+///
+/// Parses from / formats to `text[;color=<color>][;icon=<name>]`, where a
+/// `;` or `\` in the text is escaped with a `\`:
+///
+/// ```
+/// use badgeland::SegmentSpec;
+///
+/// let segment: SegmentSpec = "passing;color=brand;icon=si:github".parse().unwrap();
+/// assert_eq!(segment.text, "passing");
+/// assert_eq!(segment.to_string(), "passing;color=brand;icon=si:github");
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde_de", derive(Serialize, Deserialize))]
+pub struct SegmentSpec {
+    pub text: String,
+    /// A css color, or `brand` for the segment icon's brand color
+    #[cfg_attr(
+        feature = "serde_de",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub color: Option<BadgeColor>,
+    #[cfg_attr(
+        feature = "serde_de",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub icon: Option<String>,
+}
+
+impl FromStr for SegmentSpec {
+    type Err = SpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on unescaped `;`
+        let mut parts = vec![String::new()];
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(e @ ('\\' | ';')) => parts.last_mut().unwrap().push(e),
+                    _ => return Err(SpecError::Segment(s.to_string())),
+                },
+                ';' => parts.push(String::new()),
+                c => parts.last_mut().unwrap().push(c),
+            }
+        }
+
+        let mut parts = parts.into_iter();
+        let mut segment = SegmentSpec {
+            text: parts.next().unwrap_or_default(),
+            ..SegmentSpec::default()
+        };
+        for attr in parts {
+            match attr.split_once('=') {
+                Some(("color", c)) => segment.color = Some(c.parse()?),
+                Some(("icon", i)) => segment.icon = Some(i.to_string()),
+                _ => return Err(SpecError::Segment(s.to_string())),
+            }
+        }
+        Ok(segment)
+    }
+}
+
+impl fmt::Display for SegmentSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.text.chars() {
+            if matches!(c, '\\' | ';') {
+                f.write_str("\\")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        if let Some(c) = &self.color {
+            write!(f, ";color={}", c)?;
+        }
+        if let Some(i) = &self.icon {
+            write!(f, ";icon={}", i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Owned description of a badge.
+///
+/// Unlike [`Badge`] it can be stored, cloned and sent across threads. It
+/// serializes (with `serde_de`) to the same json shape the web `/url/`
+/// endpoint accepts, and parses from / formats to a url query string:
+///
+/// ```
+/// use badgeland::BadgeSpec;
+///
+/// let spec: BadgeSpec = "subject=build&text=passing&color=green".parse().unwrap();
+/// assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+/// let svg = spec.render();
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+    feature = "serde_de",
+    derive(Serialize, Deserialize),
+    serde(try_from = "SpecFields", into = "SpecFields")
+)]
+pub struct BadgeSpec {
+    pub subject: Option<String>,
+    pub content: BadgeContent,
+    /// A css color, or `brand` for the icon's brand color
+    pub color: Option<BadgeColor>,
+    /// Picks `color` from the badge value, see [`Badge::color_scale`]
+    pub color_scale: Option<ColorScale>,
+    pub icon: Option<String>,
+    /// A css color, or `brand` for the icon's brand color
+    pub icon_color: Option<BadgeColor>,
+    pub size: Option<Size>,
+    pub style: Option<Style>,
+    pub chart: Option<Chart>,
+    /// Shortens long text to fit, see [`Badge::max_width`]
+    pub max_width: Option<usize>,
+    /// Chart range, see [`Badge::y_min`] and [`Badge::y_max`]
+    pub y_min: Option<f32>,
+    pub y_max: Option<f32>,
+    /// Svg id prefix, see [`Badge::id_prefix`]. Skipped when invalid
+    pub id_prefix: Option<String>,
+    /// Derive the id prefix from the badge, takes precedence over `id_prefix`
+    pub hashed_ids: bool,
+    /// See [`Badge::outline_text`]
+    pub outline_text: bool,
+    /// See [`Badge::mirror_segments`]
+    pub mirror_segments: bool,
+}
+
+impl BadgeSpec {
+    /// Fill every unset option from `other`.
+    pub fn or(self, other: BadgeSpec) -> BadgeSpec {
+        BadgeSpec {
+            subject: self.subject.or(other.subject),
+            content: match self.content {
+                BadgeContent::None => other.content,
+                c => c,
+            },
+            color: self.color.or(other.color),
+            color_scale: self.color_scale.or(other.color_scale),
+            icon: self.icon.or(other.icon),
+            icon_color: self.icon_color.or(other.icon_color),
+            size: self.size.or(other.size),
+            style: self.style.or(other.style),
+            chart: self.chart.or(other.chart),
+            max_width: self.max_width.or(other.max_width),
+            y_min: self.y_min.or(other.y_min),
+            y_max: self.y_max.or(other.y_max),
+            id_prefix: self.id_prefix.or(other.id_prefix),
+            hashed_ids: self.hashed_ids || other.hashed_ids,
+            outline_text: self.outline_text || other.outline_text,
+            mirror_segments: self.mirror_segments || other.mirror_segments,
+        }
+    }
+
+    /// Render the badge to svg. Unknown icon names and invalid id prefixes are skipped.
+    pub fn render(&self) -> String {
+        self.render_with_icons(&IconRegistry::new())
+    }
+
+    /// Render, looking the icon name up in `icons`.
+    pub fn render_with_icons(&self, icons: &IconRegistry) -> String {
+        let icon = self.icon.as_deref().and_then(|i| icons.get(i).ok());
+        self.render_with(icon, icons)
+    }
+
+    /// Render with `icon` in place of the named icon, e.g. one from `Icon::from_svg`.
+    pub fn render_with_icon(&self, icon: Icon) -> String {
+        self.render_with(Some(icon), &IconRegistry::new())
+    }
+
+    fn render_with(&self, icon: Option<Icon>, icons: &IconRegistry) -> String {
+        let mut badge = Badge::new();
+
+        let brand = icon.as_ref().and_then(|i| i.meta()).and_then(|m| m.color());
+        let resolve = |c: &BadgeColor| resolve_color(c, &brand);
+
+        if let Some(s) = &self.subject {
+            badge.subject(s);
+        }
+        if let Some(c) = self.color.as_ref().and_then(resolve) {
+            badge.color(c);
+        }
+        if let Some(c) = &self.color_scale {
+            badge.color_scale(c);
+        }
+        if let Some(s) = self.size {
+            badge.size(s);
+        }
+        if let Some(s) = self.style {
+            badge.style(s);
+        }
+        if let Some(c) = self.chart {
+            badge.chart(c);
+        }
+        if let Some(w) = self.max_width {
+            badge.max_width(w);
+        }
+        if let Some(min) = self.y_min {
+            badge.y_min(min);
+        }
+        if let Some(max) = self.y_max {
+            badge.y_max(max);
+        }
+        if let Some(p) = &self.id_prefix {
+            let _ = badge.id_prefix(p);
+        }
+        if self.hashed_ids {
+            badge.hashed_ids();
+        }
+        if self.outline_text {
+            badge.outline_text();
+        }
+        if self.mirror_segments {
+            badge.mirror_segments();
+        }
+
+        if let Some(icon) = icon {
+            badge.icon(icon);
+            if let Some(c) = self.icon_color.as_ref().and_then(resolve) {
+                badge.icon_color(c);
+            }
+        }
+
+        match &self.content {
+            BadgeContent::Text(t) => badge.text(t).to_string(),
+            BadgeContent::Data(d) => badge.data(d.as_ref()).to_string(),
+            BadgeContent::Segments(specs) => {
+                let segments: Vec<_> = specs
+                    .iter()
+                    .map(|s| {
+                        let icon = s.icon.as_deref().and_then(|i| icons.get(i).ok());
+                        let brand = icon.as_ref().and_then(|i| i.meta()).and_then(|m| m.color());
+                        let mut segment = Segment::new(&s.text);
+                        if let Some(c) = s.color.as_ref().and_then(|c| resolve_color(c, &brand)) {
+                            segment = segment.color(c);
+                        }
+                        if let Some(icon) = icon {
+                            segment = segment.icon(icon);
+                        }
+                        segment
+                    })
+                    .collect();
+                badge.segments(&segments).to_string()
+            }
+            BadgeContent::Progress(p) => badge.progress(*p).to_string(),
+            BadgeContent::None => badge.to_string(),
+        }
+    }
+}
+
+fn id_prefix(prefix: String) -> Result<String, IdPrefixError> {
+    match valid_id_prefix(&prefix) {
+        true => Ok(prefix),
+        false => Err(IdPrefixError),
+    }
+}
+
+// `brand` is skipped for icons without a brand color
+fn resolve_color(color: &BadgeColor, brand: &Option<Color>) -> Option<Color> {
+    match color {
+        BadgeColor::Brand => brand.clone(),
+        BadgeColor::Css(c) => Some(c.clone()),
+    }
+}
+
+impl FromStr for BadgeSpec {
+    type Err = SpecError;
+
+    /// Parse a url query string such as `subject=build&text=passing&style=flat`.
+    /// Unknown keys are ignored so the spec can share a query with other params.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut spec = BadgeSpec::default();
+
+        for pair in s
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|p| !p.is_empty())
+        {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            // Only values of known keys are decoded, others may use another encoding
+            let value = || decode(value);
+            match key {
+                "subject" => spec.subject = Some(value()?),
+                "text" => spec.content = BadgeContent::Text(value()?),
+                "data" => spec.content = BadgeContent::Data(value()?.parse()?),
+                "progress" => {
+                    let v = value()?;
+                    let p = v.parse().map_err(|_| SpecError::Progress(v))?;
+                    spec.content = BadgeContent::Progress(p);
+                }
+                // Repeated in order, one key per segment
+                "segment" => {
+                    let segment = value()?.parse()?;
+                    match &mut spec.content {
+                        BadgeContent::Segments(s) => s.push(segment),
+                        c => *c = BadgeContent::Segments(vec![segment]),
+                    }
+                }
+                "color" => spec.color = Some(value()?.parse()?),
+                "color_scale" => spec.color_scale = Some(value()?.parse()?),
+                "icon" => spec.icon = Some(value()?),
+                "icon_color" => spec.icon_color = Some(value()?.parse()?),
+                "size" => spec.size = Some(value()?.parse()?),
+                "style" => spec.style = Some(value()?.parse()?),
+                "chart" => spec.chart = Some(value()?.parse()?),
+                "max_width" => spec.max_width = Some(value()?.parse()?),
+                "y_min" => spec.y_min = Some(value()?.parse()?),
+                "y_max" => spec.y_max = Some(value()?.parse()?),
+                "id_prefix" => spec.id_prefix = Some(id_prefix(value()?)?),
+                "hashed_ids" => spec.hashed_ids = value()?.parse()?,
+                "outline_text" => spec.outline_text = value()?.parse()?,
+                "mirror_segments" => spec.mirror_segments = value()?.parse()?,
+                _ => {}
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for BadgeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = match &self.content {
+            BadgeContent::Data(d) => Some(
+                d.as_ref()
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => None,
+        };
+        let progress = match &self.content {
+            BadgeContent::Progress(p) => Some(p.to_string()),
+            _ => None,
+        };
+        let text = match &self.content {
+            BadgeContent::Text(t) => Some(t.as_str()),
+            _ => None,
+        };
+        let color = self.color.as_ref().map(|c| c.to_string());
+        let color_scale = self.color_scale.as_ref().map(|c| c.to_string());
+        let icon_color = self.icon_color.as_ref().map(|c| c.to_string());
+        let size = self.size.map(|s| s.to_string());
+        let style = self.style.map(|s| s.to_string());
+        let chart = self.chart.map(|c| c.to_string());
+        let max_width = self.max_width.map(|w| w.to_string());
+        let y_min = self.y_min.map(|v| v.to_string());
+        let y_max = self.y_max.map(|v| v.to_string());
+        // Flags are only written when set
+        let flag = |set: bool| set.then_some("true");
+
+        let pairs = [
+            ("subject", self.subject.as_deref()),
+            ("text", text),
+            ("data", data.as_deref()),
+            ("progress", progress.as_deref()),
+            ("color", color.as_deref()),
+            ("color_scale", color_scale.as_deref()),
+            ("icon", self.icon.as_deref()),
+            ("icon_color", icon_color.as_deref()),
+            ("size", size.as_deref()),
+            ("style", style.as_deref()),
+            ("chart", chart.as_deref()),
+            ("max_width", max_width.as_deref()),
+            ("y_min", y_min.as_deref()),
+            ("y_max", y_max.as_deref()),
+            ("id_prefix", self.id_prefix.as_deref()),
+            ("hashed_ids", flag(self.hashed_ids)),
+            ("outline_text", flag(self.outline_text)),
+            ("mirror_segments", flag(self.mirror_segments)),
+        ];
+
+        let segments = match &self.content {
+            BadgeContent::Segments(s) => s.iter().map(|s| s.to_string()).collect(),
+            _ => vec![],
+        };
+        let segments = segments.iter().map(|s| ("segment", Some(s.as_str())));
+
+        let mut sep = "";
+        for (key, value) in pairs.into_iter().chain(segments) {
+            if let Some(v) = value {
+                write!(f, "{}{}=", sep, key)?;
+                encode(f, v)?;
+                sep = "&";
+            }
+        }
+        Ok(())
+    }
+}
+
+fn encode(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                write!(f, "{}", b as char)?
+            }
+            _ => write!(f, "%{:02X}", b)?,
+        }
+    }
+    Ok(())
+}
+
+fn decode(s: &str) -> Result<String, SpecError> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = [bytes.next(), bytes.next()];
+                let byte = match hex {
+                    [Some(h), Some(l)] => std::str::from_utf8(&[h, l])
+                        .ok()
+                        .and_then(|h| u8::from_str_radix(h, 16).ok()),
+                    _ => None,
+                };
+                out.push(byte.ok_or_else(|| SpecError::Query(s.to_string()))?);
+            }
+            b => out.push(b),
+        }
+    }
+    String::from_utf8(out).map_err(|_| SpecError::Query(s.to_string()))
+}
+
+// Flat field layout shared with the web `/url/` json source format.
+#[cfg(feature = "serde_de")]
+#[derive(Serialize, Deserialize)]
+struct SpecFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<BadgeData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    segments: Option<Vec<SegmentSpec>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progress: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<BadgeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color_scale: Option<ColorScale>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon_color: Option<BadgeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<Size>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    style: Option<Style>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chart: Option<Chart>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_width: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y_min: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y_max: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Not::not")]
+    hashed_ids: bool,
+    #[serde(default, skip_serializing_if = "Not::not")]
+    outline_text: bool,
+    #[serde(default, skip_serializing_if = "Not::not")]
+    mirror_segments: bool,
+}
+
+#[cfg(feature = "serde_de")]
+impl TryFrom<SpecFields> for BadgeSpec {
+    type Error = SpecError;
+
+    fn try_from(f: SpecFields) -> Result<Self, Self::Error> {
+        let content = match (f.segments, f.data, f.progress, f.text) {
+            (Some(s), ..) => BadgeContent::Segments(s),
+            (_, Some(d), ..) => BadgeContent::Data(d),
+            (_, _, Some(p), _) => BadgeContent::Progress(p),
+            (.., Some(t)) => BadgeContent::Text(t),
+            _ => BadgeContent::None,
+        };
+        Ok(BadgeSpec {
+            subject: f.subject,
+            content,
+            color: f.color,
+            color_scale: f.color_scale,
+            icon: f.icon,
+            icon_color: f.icon_color,
+            size: f.size,
+            style: f.style,
+            chart: f.chart,
+            max_width: f.max_width,
+            y_min: f.y_min,
+            y_max: f.y_max,
+            id_prefix: f.id_prefix.map(id_prefix).transpose()?,
+            hashed_ids: f.hashed_ids,
+            outline_text: f.outline_text,
+            mirror_segments: f.mirror_segments,
+        })
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl From<BadgeSpec> for SpecFields {
+    fn from(s: BadgeSpec) -> Self {
+        let (mut text, mut data, mut segments, mut progress) = (None, None, None, None);
+        match s.content {
+            BadgeContent::Text(t) => text = Some(t),
+            BadgeContent::Data(d) => data = Some(d),
+            BadgeContent::Segments(s) => segments = Some(s),
+            BadgeContent::Progress(p) => progress = Some(p),
+            BadgeContent::None => {}
+        }
+        SpecFields {
+            subject: s.subject,
+            text,
+            data,
+            segments,
+            progress,
+            color: s.color,
+            color_scale: s.color_scale,
+            icon: s.icon,
+            icon_color: s.icon_color,
+            size: s.size,
+            style: s.style,
+            chart: s.chart,
+            max_width: s.max_width,
+            y_min: s.y_min,
+            y_max: s.y_max,
+            id_prefix: s.id_prefix,
+            hashed_ids: s.hashed_ids,
+            outline_text: s.outline_text,
+            mirror_segments: s.mirror_segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BadgeContent, BadgeSpec, SegmentSpec, SpecError};
+    use crate::{Badge, BadgeColor, BadgeData, Chart, Color, IconRegistry, Segment, Size, Style};
+
+    fn full_spec() -> BadgeSpec {
+        BadgeSpec {
+            subject: Some("a & b=c".into()),
+            content: BadgeContent::Text("100% ✓".into()),
+            color: Some("red".parse().unwrap()),
+            color_scale: None,
+            icon: Some("git".into()),
+            icon_color: Some(Color::black().into()),
+            size: Some(Size::Large),
+            style: Some(Style::Social),
+            chart: None,
+            max_width: Some(120),
+            ..BadgeSpec::default()
+        }
+    }
+
+    // Every option set, with `content` as the badge content
+    fn every_option(content: BadgeContent) -> BadgeSpec {
+        BadgeSpec {
+            subject: Some("a & b=c".into()),
+            content,
+            color: Some(BadgeColor::Brand),
+            color_scale: Some("0:red..100:green".parse().unwrap()),
+            icon: Some("fa:git".into()),
+            icon_color: Some(Color::black().into()),
+            size: Some(Size::Custom(32)),
+            style: Some(Style::ForTheBadge),
+            chart: Some(Chart::Area),
+            max_width: Some(120),
+            y_min: Some(-1.5),
+            y_max: Some(10.),
+            id_prefix: Some("b1-".into()),
+            hashed_ids: true,
+            outline_text: true,
+            mirror_segments: true,
+        }
+    }
+
+    #[test]
+    fn every_option_round_trips() {
+        for content in [
+            BadgeContent::Text("100% ✓".into()),
+            BadgeContent::Data(BadgeData(vec![1., 2.5, -3.].into())),
+            BadgeContent::Segments(vec![
+                SegmentSpec {
+                    text: "a;b\\c".into(),
+                    ..SegmentSpec::default()
+                },
+                "x&y=z;color=brand;icon=fa:git".parse().unwrap(),
+                "rgb(1, 2, 3);color=rgb(1, 2, 3)".parse().unwrap(),
+            ]),
+            BadgeContent::Progress(87.5),
+        ] {
+            let spec = every_option(content);
+            assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+            #[cfg(feature = "serde_de")]
+            {
+                let json = serde_json::to_string(&spec).unwrap();
+                assert_eq!(serde_json::from_str::<BadgeSpec>(&json).unwrap(), spec);
+            }
+        }
+    }
+
+    #[test]
+    fn hostile_id_prefix_is_rejected() {
+        let query = "style=social&id_prefix=x%7B%7D*%7Bfill:url(https://evil.example/t)%7D%20";
+        assert!(matches!(
+            query.parse::<BadgeSpec>(),
+            Err(SpecError::IdPrefix(_))
+        ));
+        #[cfg(feature = "serde_de")]
+        {
+            let json = r#"{"text":"b","id_prefix":"x{}*{}"}"#;
+            assert!(serde_json::from_str::<BadgeSpec>(json).is_err());
+        }
+
+        let spec = BadgeSpec {
+            subject: Some("a".into()),
+            content: BadgeContent::Text("b".into()),
+            style: Some(Style::Social),
+            id_prefix: Some("x{}*{fill:url(https://evil.example/t)} ".into()),
+            ..BadgeSpec::default()
+        };
+        let svg = spec.render();
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let css = doc.descendants().find(|n| n.has_tag_name("style")).unwrap();
+        let css = css.text().unwrap().trim();
+        assert!(css.starts_with("a:hover #llink{"), "{}", css);
+        assert!(!css.contains("evil"));
+    }
+
+    #[test]
+    fn render_applies_every_option() {
+        let spec: BadgeSpec = "data=2,4&y_min=0&y_max=8&id_prefix=b1-&outline_text=true"
+            .parse()
+            .unwrap();
+        let mut badge = Badge::new();
+        badge
+            .y_min(0.)
+            .y_max(8.)
+            .id_prefix("b1-")
+            .unwrap()
+            .outline_text();
+        assert_eq!(spec.render(), badge.data(&[2., 4.]).to_string());
+
+        let spec: BadgeSpec = "text=x&id_prefix=b1-&hashed_ids=true".parse().unwrap();
+        let mut badge = Badge::new();
+        badge.hashed_ids();
+        assert_eq!(spec.render(), badge.text("x").to_string());
+        assert!("outline_text=yes".parse::<BadgeSpec>().is_err());
+
+        let spec: BadgeSpec = "subject=coverage&progress=87.5".parse().unwrap();
+        assert_eq!(spec.content, BadgeContent::Progress(87.5));
+        let mut badge = Badge::new();
+        badge.subject("coverage");
+        assert_eq!(spec.render(), badge.progress(87.5).to_string());
+        assert!("progress=lots".parse::<BadgeSpec>().is_err());
+    }
+
+    #[test]
+    fn segment_from_str() {
+        let segment: SegmentSpec = r"a\;b\\;color=green;icon=dot".parse().unwrap();
+        assert_eq!(segment.text, r"a;b\");
+        assert_eq!(segment.color, Some("green".parse().unwrap()));
+        assert_eq!(segment.icon.as_deref(), Some("dot"));
+        assert_eq!(segment.to_string().parse::<SegmentSpec>().unwrap(), segment);
+
+        assert_eq!("".parse::<SegmentSpec>().unwrap(), SegmentSpec::default());
+        assert!("a;size=2".parse::<SegmentSpec>().is_err());
+        assert!("a;color=notacolor".parse::<SegmentSpec>().is_err());
+        assert!(r"a\b".parse::<SegmentSpec>().is_err());
+    }
+
+    #[test]
+    fn render_with_segments() {
+        let mut icons = IconRegistry::new();
+        let sprite = r#"<svg><symbol id="dot" viewBox="0 0 2 2"><circle r="1"/></symbol></svg>"#;
+        icons.add_sprite(sprite).unwrap();
+        let query = "subject=build&segment=linux&segment=passing;color=green;icon=dot";
+        let spec: BadgeSpec = format!("{}&mirror_segments=true", query).parse().unwrap();
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+
+        let segments = [
+            Segment::new("linux"),
+            Segment::new("passing")
+                .color("green".parse().unwrap())
+                .icon(icons.get("dot").unwrap()),
+        ];
+        let mut badge = Badge::new();
+        badge.subject("build").mirror_segments();
+        assert_eq!(
+            spec.render_with_icons(&icons),
+            badge.segments(&segments).to_string()
+        );
+    }
+
+    #[test]
+    fn query_round_trip() {
+        let spec = full_spec();
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+
+        let spec = BadgeSpec {
+            content: BadgeContent::Data(BadgeData(vec![1., 2.5, -3.].into())),
+            chart: Some(Chart::Bars),
+            ..BadgeSpec::default()
+        };
+        assert_eq!(spec.to_string(), "data=1%2C2.5%2C-3&chart=Bars");
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+
+        let spec = BadgeSpec {
+            content: BadgeContent::Text("87%".into()),
+            color_scale: Some("0:red..100:rgb(0, 128, 0)".parse().unwrap()),
+            ..BadgeSpec::default()
+        };
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+    }
+
+    #[test]
+    fn query_parse() {
+        let spec: BadgeSpec = "?source=x&subject=build+ci&text=ok&style=flat&size=m"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.subject.as_deref(), Some("build ci"));
+        assert_eq!(spec.content, BadgeContent::Text("ok".into()));
+        assert_eq!(spec.style, Some(Style::Flat));
+        assert_eq!(spec.size, Some(Size::Medium));
+
+        assert!("color=notacolor".parse::<BadgeSpec>().is_err());
+        assert!("color_scale=red".parse::<BadgeSpec>().is_err());
+        assert!("data=1,x".parse::<BadgeSpec>().is_err());
+        assert!("size=2".parse::<BadgeSpec>().is_err());
+
+        let spec: BadgeSpec = "text=ok&size=64".parse().unwrap();
+        assert_eq!(spec.size, Some(Size::Custom(64)));
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+        assert!("subject=%zz".parse::<BadgeSpec>().is_err());
+        let spec: BadgeSpec = "subject=ok&source=%zz&q=%".parse().unwrap();
+        assert_eq!(spec.subject.as_deref(), Some("ok"));
+        assert!("max_width=-1".parse::<BadgeSpec>().is_err());
+
+        let spec: BadgeSpec = "style=for-the-badge".parse().unwrap();
+        assert_eq!(spec.style, Some(Style::ForTheBadge));
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+    }
+
+    #[test]
+    fn content_from_str() {
+        assert_eq!(
+            "1,2".parse::<BadgeContent>().unwrap(),
+            BadgeContent::Data(BadgeData(vec![1., 2.].into()))
+        );
+        assert_eq!(
+            "12".parse::<BadgeContent>().unwrap(),
+            BadgeContent::Text("12".into())
+        );
+    }
+
+    #[test]
+    fn or_prefers_self() {
+        let query: BadgeSpec = "color=blue&size=small".parse().unwrap();
+        let merged = query.or(full_spec());
+        assert_eq!(merged.color, Some("blue".parse().unwrap()));
+        assert_eq!(merged.size, Some(Size::Small));
+        assert_eq!(merged.subject, full_spec().subject);
+        assert_eq!(merged.content, full_spec().content);
+    }
+
+    #[test]
+    fn render_matches_badge() {
+        let spec: BadgeSpec = "subject=build&text=passing&color=green&style=flat"
+            .parse()
+            .unwrap();
+        let mut badge = Badge::new();
+        badge
+            .subject("build")
+            .color("green".parse().unwrap())
+            .style(Style::Flat);
+        assert_eq!(spec.render(), badge.text("passing").to_string());
+    }
+
+    #[test]
+    fn render_with_color_scale() {
+        let spec: BadgeSpec = "text=87%25&color_scale=0:red,80:green".parse().unwrap();
+        let scale = spec.color_scale.clone().unwrap();
+        let mut badge = Badge::new();
+        badge.color_scale(&scale);
+        let svg = spec.render();
+        assert_eq!(svg, badge.text("87%").to_string());
+        assert!(svg.contains(scale.color(87.).unwrap().as_ref()));
+    }
+
+    #[test]
+    fn render_with_registry_icon() {
+        let mut icons = IconRegistry::new();
+        let sprite = r#"<svg><symbol id="dot" viewBox="0 0 2 2"><circle r="1"/></symbol></svg>"#;
+        icons.add_sprite(sprite).unwrap();
+        let spec: BadgeSpec = "subject=x&text=y&icon=dot".parse().unwrap();
+
+        let mut badge = Badge::new();
+        badge.subject("x").icon(icons.get("dot").unwrap());
+        assert_eq!(spec.render_with_icons(&icons), badge.text("y").to_string());
+        assert!(!spec.render().contains("<symbol"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "icons-simple", feature = "icons-fa-solid"))]
+    fn render_with_brand_color() {
+        let spec: BadgeSpec = "subject=x&text=y&icon=si:npm&color=brand&icon_color=brand"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.to_string().parse::<BadgeSpec>().unwrap(), spec);
+
+        let icon = crate::Icon::try_from("si:npm").unwrap();
+        let brand = icon.meta().unwrap().color().unwrap();
+        let mut badge = Badge::new();
+        badge
+            .subject("x")
+            .color(brand.clone())
+            .icon(icon)
+            .icon_color(brand);
+        assert_eq!(spec.render(), badge.text("y").to_string());
+
+        // Ignored for icons without a brand color
+        let spec: BadgeSpec = "subject=x&text=y&icon=fa:code&color=brand".parse().unwrap();
+        let mut badge = Badge::new();
+        badge
+            .subject("x")
+            .icon(crate::Icon::try_from("fa:code").unwrap());
+        assert_eq!(spec.render(), badge.text("y").to_string());
+    }
+
+    #[test]
+    fn spec_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<BadgeSpec>();
+    }
+
+    #[cfg(feature = "serde_de")]
+    mod serde_tests {
+        use super::{full_spec, BadgeContent, BadgeSpec};
+        use crate::BadgeData;
+        use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+        #[test]
+        fn spec_tokens() {
+            let spec = BadgeSpec {
+                subject: Some("s".into()),
+                content: BadgeContent::Data(BadgeData(vec![1., 2.].into())),
+                ..BadgeSpec::default()
+            };
+            assert_tokens(
+                &spec,
+                &[
+                    Token::Struct {
+                        name: "SpecFields",
+                        len: 2,
+                    },
+                    Token::Str("subject"),
+                    Token::Some,
+                    Token::Str("s"),
+                    Token::Str("data"),
+                    Token::Some,
+                    Token::Seq { len: Some(2) },
+                    Token::F32(1.),
+                    Token::F32(2.),
+                    Token::SeqEnd,
+                    Token::StructEnd,
+                ],
+            );
+        }
+
+        #[test]
+        fn spec_deserialize_web_options() {
+            let spec = full_spec();
+            assert_de_tokens(
+                &spec,
+                &[
+                    Token::Map { len: None },
+                    Token::Str("text"),
+                    Token::Some,
+                    Token::Str("100% ✓"),
+                    Token::Str("subject"),
+                    Token::Some,
+                    Token::Str("a & b=c"),
+                    Token::Str("style"),
+                    Token::Some,
+                    Token::Str("social"),
+                    Token::Str("size"),
+                    Token::Some,
+                    Token::Str("Large"),
+                    Token::Str("color"),
+                    Token::Some,
+                    Token::Str("red"),
+                    Token::Str("icon"),
+                    Token::Some,
+                    Token::Str("git"),
+                    Token::Str("icon_color"),
+                    Token::Some,
+                    Token::Str("black"),
+                    Token::Str("max_width"),
+                    Token::Some,
+                    Token::U64(120),
+                    Token::MapEnd,
+                ],
+            );
+        }
+    }
+}
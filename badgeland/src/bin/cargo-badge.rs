@@ -7,49 +7,66 @@ Install using `cargo install badgeland --features cli`
 Fast badge generator for any purpose
 
 USAGE:
-    cargo badge [OPTIONS] <CONTENT>
+    cargo badge [OPTIONS] [CONTENT]
 
 ARGS:
     <CONTENT>    Badge content. Can be string or csv
 
 OPTIONS:
     -c, --classic                    Classic badge style (Default)
-        --color <COLOR>              Badge color. Must be a valid css color
+        --chart <CHART>              Chart kind for data badges [possible values: line, area,
+                                     bars]
+        --color <COLOR>              Badge color. Must be a valid css color, or `brand` for the
+                                     icon's brand color
+        --color-scale <COLOR_SCALE>  Pick the color from the badge value, e.g.
+                                     `0:red,50:yellow,80:green` or `0:red..100:green`
     -f, --flat                       Flat badge style
     -z  --social                     Social badge style
+    -b, --for-the-badge              Taller badge with uppercase, letter-spaced text
+        --hashed-ids                 Prefix svg ids with a hash of the badge
+        --height <HEIGHT>            Badge height in px
     -h, --help                       Print help information
         --icon <ICON>                Badge icon. Icons are from
                                      <https://fontawesome.com/search?s=brands>,
                                      <https://fontawesome.com/search?s=solid> and
                                      <https://simpleicons.org/>
-        --icon-color <ICON_COLOR>    Icon color. Must be a valid css color
+        --icon-file <ICON_FILE>      Badge icon from an svg file
+        --icon-color <ICON_COLOR>    Icon color. Must be a valid css color, or `brand` for the
+                                     icon's brand color
+        --icon-sprite <ICON_SPRITE>  Svg sprite with more icons, one `<symbol id="...">` per
+                                     icon
+        --id-prefix <ID_PREFIX>      Prefix every svg id, to inline several badges in one html
+                                     page
     -l, --large                      Large badge size
     -m, --medium                     Medium badge size
+        --max-width <MAX_WIDTH>      Shorten subject and text with an ellipsis to fit this width
+                                     in px
+        --mirror-segments            Draw segments from right to left, for rtl languages
     -o, --out <OUT>                  Output svg to file
+        --outline-text               Draw text as glyph outlines, so it looks the same without
+                                     the font
+    -p, --plastic                    Classic badge style with a glossy gradient
+        --progress <PROGRESS>        Progress bar filled to this percentage, in place of the
+                                     content
     -s, --subject <SUBJECT>          Badge subject
+        --segment <SEGMENT>          Segment drawn after the subject,
+                                     `text[;color=<COLOR>][;icon=<ICON>]`. Repeat for more
+                                     segments, in place of the content
     -x, --small                      Small badge size (Default)
+        --y-max <Y_MAX>              Value drawn at the top of the chart. Defaults to the data
+                                     maximum
+        --y-min <Y_MIN>              Value drawn at the bottom of the chart. Defaults to the data
+                                     minimum
 ```
 
 */
 
-use badgeland::{icon_exists, Badge, BadgeData, Color, Icon, Size, Style};
+use badgeland::{
+    Badge, BadgeColor, BadgeContent, BadgeSpec, Chart, ColorScale, Icon, IconRegistry,
+    IdPrefixError, SegmentSpec, Size, Style,
+};
 use clap::{ArgGroup, Parser};
-use std::{convert::TryFrom, error::Error, fs::File, io::prelude::*, path::PathBuf, str::FromStr};
-
-#[derive(Debug, PartialEq, Clone)]
-enum Content {
-    Text(String),
-    Data(BadgeData),
-}
-
-impl FromStr for Content {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        BadgeData::from_str(s)
-            .map(|d| Content::Data(d))
-            .or(Ok(Content::Text(s.to_string())))
-    }
-}
+use std::{error::Error, fs, fs::File, io::prelude::*, path::PathBuf};
 
 #[derive(Parser, Debug)]
 #[clap(group = ArgGroup::new("style").required(false))]
@@ -65,6 +82,14 @@ struct StyleArg {
     /// Social badge style
     #[clap(short = 'z', long, group = "style", action)]
     social: bool,
+
+    /// Taller badge with uppercase, letter-spaced text
+    #[clap(short = 'b', long, group = "style", action)]
+    for_the_badge: bool,
+
+    /// Classic badge style with a glossy gradient
+    #[clap(short, long, group = "style", action)]
+    plastic: bool,
 }
 
 impl From<StyleArg> for Style {
@@ -73,6 +98,10 @@ impl From<StyleArg> for Style {
             Self::Flat
         } else if s.social {
             Self::Social
+        } else if s.for_the_badge {
+            Self::ForTheBadge
+        } else if s.plastic {
+            Self::Plastic
         } else {
             Self::Classic
         }
@@ -93,13 +122,33 @@ struct SizeArg {
     /// Large badge size
     #[clap(short, long, group = "size", action)]
     large: bool,
+
+    /// Badge height in px
+    #[clap(long, group = "size", value_parser = parse_height)]
+    height: Option<Size>,
+}
+
+fn parse_height(s: &str) -> Result<Size, String> {
+    match s.parse() {
+        Ok(size @ Size::Custom(_)) => Ok(size),
+        _ => {
+            let (min, max) = Size::CUSTOM_RANGE.into_inner();
+            Err(format!("must be between {} and {} px", min, max))
+        }
+    }
+}
+
+fn parse_id_prefix(s: &str) -> Result<String, IdPrefixError> {
+    Badge::new().id_prefix(s)?;
+    Ok(s.to_string())
 }
 
 impl From<SizeArg> for Size {
     fn from(s: SizeArg) -> Self {
-        match (s.large, s.medium, s.small) {
-            (true, _, _) => Self::Large,
-            (_, true, _) => Self::Medium,
+        match (s.height, s.large, s.medium, s.small) {
+            (Some(size), ..) => size,
+            (_, true, _, _) => Self::Large,
+            (_, _, true, _) => Self::Medium,
             _ => Self::Small,
         }
     }
@@ -118,25 +167,105 @@ struct Opt {
     #[clap(flatten)]
     size: SizeArg,
 
-    /// Badge color. Must be a valid css color
+    /// Badge color. Must be a valid css color, or `brand` for the icon's brand color
+    #[clap(long, value_parser)]
+    color: Option<BadgeColor>,
+
+    /// Pick the color from the badge value, e.g. `0:red,50:yellow,80:green` or `0:red..100:green`
     #[clap(long, value_parser)]
-    color: Option<Color>,
+    color_scale: Option<ColorScale>,
 
     /// Badge icon. Icons are from <https://fontawesome.com/search?s=brands>, <https://fontawesome.com/search?s=solid> and <https://simpleicons.org/>
     #[clap(long, value_parser)]
     icon: Option<String>,
 
-    /// Icon color. Must be a valid css color
+    /// Badge icon from an svg file
+    #[clap(long, value_parser, conflicts_with = "icon")]
+    icon_file: Option<PathBuf>,
+
+    /// Svg sprite with more icons, one `<symbol id="...">` per icon
+    #[clap(long, value_parser)]
+    icon_sprite: Vec<PathBuf>,
+
+    /// Icon color. Must be a valid css color, or `brand` for the icon's brand color
+    #[clap(long, value_parser)]
+    icon_color: Option<BadgeColor>,
+
+    /// Chart kind for data badges [possible values: line, area, bars]
     #[clap(long, value_parser)]
-    icon_color: Option<Color>,
+    chart: Option<Chart>,
+
+    /// Shorten subject and text with an ellipsis to fit this width in px
+    #[clap(long, value_parser)]
+    max_width: Option<usize>,
+
+    /// Value drawn at the bottom of the chart. Defaults to the data minimum
+    #[clap(long, value_parser, allow_hyphen_values = true)]
+    y_min: Option<f32>,
+
+    /// Value drawn at the top of the chart. Defaults to the data maximum
+    #[clap(long, value_parser, allow_hyphen_values = true)]
+    y_max: Option<f32>,
+
+    /// Prefix every svg id, to inline several badges in one html page
+    #[clap(long, value_parser = parse_id_prefix)]
+    id_prefix: Option<String>,
+
+    /// Prefix svg ids with a hash of the badge
+    #[clap(long, action, conflicts_with = "id_prefix")]
+    hashed_ids: bool,
+
+    /// Draw text as glyph outlines, so it looks the same without the font
+    #[clap(long, action)]
+    outline_text: bool,
+
+    /// Segment drawn after the subject, `text[;color=<COLOR>][;icon=<ICON>]`. Repeat for more
+    /// segments, in place of the content
+    #[clap(long, value_parser)]
+    segment: Vec<SegmentSpec>,
+
+    /// Draw segments from right to left, for rtl languages
+    #[clap(long, action)]
+    mirror_segments: bool,
+
+    /// Progress bar filled to this percentage, in place of the content
+    #[clap(long, value_parser, conflicts_with_all = ["content", "segment"])]
+    progress: Option<f32>,
 
     /// Output svg to file
     #[clap(short, long, value_parser)]
     out: Option<PathBuf>,
 
     /// Badge content. Can be string or csv
-    #[clap(value_parser)]
-    content: Content,
+    #[clap(value_parser, required_unless_present_any = ["segment", "progress"])]
+    content: Option<BadgeContent>,
+}
+
+impl From<Opt> for BadgeSpec {
+    fn from(opt: Opt) -> Self {
+        BadgeSpec {
+            subject: opt.subject,
+            content: match (opt.progress, opt.segment) {
+                (Some(p), _) => BadgeContent::Progress(p),
+                (_, s) if !s.is_empty() => BadgeContent::Segments(s),
+                _ => opt.content.unwrap_or_default(),
+            },
+            color: opt.color,
+            color_scale: opt.color_scale,
+            icon: opt.icon,
+            icon_color: opt.icon_color,
+            size: Some(opt.size.into()),
+            style: Some(opt.style.into()),
+            chart: opt.chart,
+            max_width: opt.max_width,
+            y_min: opt.y_min,
+            y_max: opt.y_max,
+            id_prefix: opt.id_prefix,
+            hashed_ids: opt.hashed_ids,
+            outline_text: opt.outline_text,
+            mirror_segments: opt.mirror_segments,
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -149,41 +278,43 @@ enum CargoCmd {
 fn main() -> Result<(), Box<dyn Error>> {
     let badge_cmd = CargoCmd::parse();
 
-    let CargoCmd::Badge(opt) = badge_cmd;
+    let CargoCmd::Badge(mut opt) = badge_cmd;
 
-    if matches!(&opt.icon, Some(icon) if !icon_exists(icon)) {
-        return Err("Icon does not exists. Try using a fontawesome icon name".into());
+    let mut icons = IconRegistry::new();
+    for path in &opt.icon_sprite {
+        icons
+            .add_sprite_file(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
     }
 
-    let mut badge = Badge::new();
-
-    if let Some(sub) = &opt.subject {
-        badge.subject(sub);
-    }
-    if let Some(col) = opt.color {
-        badge.color(col);
+    if let Some(icon) = opt.icon.as_deref().filter(|icon| !icons.contains(icon)) {
+        let hint = match icons.search(icon, 3).as_slice() {
+            [] => "Try using a fontawesome icon name".to_string(),
+            names => format!("Did you mean {}?", names.join(", ")),
+        };
+        return Err(format!("Icon `{}` does not exist. {}", icon, hint).into());
     }
 
-    badge.style(opt.style.into());
-
-    badge.size(opt.size.into());
-
-    if let Some(icon) = &opt.icon {
-        let icon = Icon::try_from(icon.as_str());
-        if let Ok(i) = icon {
-            badge.icon(i);
-        }
-        if let Some(c) = opt.icon_color {
-            badge.icon_color(c);
+    let out = opt.out.take();
+    let icon_file = opt.icon_file.take();
+
+    let spec = BadgeSpec::from(opt);
+    let svg = match icon_file {
+        Some(path) => {
+            let svg = fs::read_to_string(&path)?;
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("icon");
+            // File names may hold characters an icon id can't
+            let name: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            let icon = Icon::from_svg(&name, &svg).map_err(|e| e.to_string())?;
+            spec.render_with_icon(icon)
         }
-    }
-
-    let svg = match opt.content {
-        Content::Data(d) => badge.data(d.as_ref()).to_string(),
-        Content::Text(t) => badge.text(&t).to_string(),
+        None => spec.render_with_icons(&icons),
     };
 
-    if let Some(out_file) = opt.out {
+    if let Some(out_file) = out {
         let mut file = File::create(&out_file).unwrap();
         file.write_all(svg.as_bytes()).unwrap();
     } else {
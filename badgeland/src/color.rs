@@ -1,18 +1,18 @@
 use cssparser::{Parser, ParserInput, ToCss};
-use cssparser_color::Color as CssColor;
+use cssparser_color::{Color as CssColor, RgbaLegacy};
 use sailfish::runtime::{Buffer, Render, RenderError};
 use std::{borrow::Cow, convert::From, fmt::Display, str::FromStr};
 
 #[cfg(feature = "serde_de")]
 use serde::{de, Deserialize, Deserializer, Serialize};
 
-use super::error::ColorError;
+use super::error::{ColorError, ColorScaleError};
 
-const DEFAULT_WHITE: &'static str = "rgb(255, 255, 255)";
-const DEFAULT_BLACK: &'static str = "rgb(0, 0, 0)";
-const DEFAULT_BLUE: &'static str = "rgb(3, 102, 214)";
-const DEFAULT_GRAY: &'static str = "rgb(246, 248, 250)";
-const DEFAULT_GRAY_DARK: &'static str = "rgb(36, 41, 46)";
+const DEFAULT_WHITE: &str = "rgb(255, 255, 255)";
+const DEFAULT_BLACK: &str = "rgb(0, 0, 0)";
+const DEFAULT_BLUE: &str = "rgb(3, 102, 214)";
+const DEFAULT_GRAY: &str = "rgb(246, 248, 250)";
+const DEFAULT_GRAY_DARK: &str = "rgb(36, 41, 46)";
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 #[cfg_attr(feature = "serde_de", derive(Serialize))]
@@ -51,7 +51,7 @@ impl FromStr for Color {
             .map_err(|_| Self::Err {})
             .and_then(|c| {
                 let mut w = String::new();
-                if matches!(c.to_css(&mut w), Err(_)) {
+                if c.to_css(&mut w).is_err() {
                     Err(Self::Err {})
                 } else {
                     Ok(Color(w.into()))
@@ -103,9 +103,256 @@ impl Render for Color {
     }
 }
 
+impl Color {
+    // sRGB components, `None` for colors outside sRGB (e.g. lab())
+    fn rgba(&self) -> Option<RgbaLegacy> {
+        let mut input = ParserInput::new(self.as_ref());
+        match CssColor::parse(&mut Parser::new(&mut input)) {
+            Ok(CssColor::Rgba(c)) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+/// A color option of a [`BadgeSpec`](crate::BadgeSpec): a css color, or
+/// `brand` for the brand color of the badge icon.
+///
+/// ```
+/// use badgeland::BadgeColor;
+///
+/// assert_eq!("brand".parse::<BadgeColor>().unwrap(), BadgeColor::Brand);
+/// assert!(matches!("red".parse().unwrap(), BadgeColor::Css(_)));
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum BadgeColor {
+    /// The icon's brand color, no color if it has none
+    Brand,
+    Css(Color),
+}
+
+impl FromStr for BadgeColor {
+    type Err = ColorError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("brand") {
+            Ok(BadgeColor::Brand)
+        } else {
+            s.parse().map(BadgeColor::Css)
+        }
+    }
+}
+
+impl From<Color> for BadgeColor {
+    #[inline]
+    fn from(value: Color) -> Self {
+        BadgeColor::Css(value)
+    }
+}
+
+impl Display for BadgeColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BadgeColor::Brand => f.write_str("brand"),
+            BadgeColor::Css(c) => c.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl Serialize for BadgeColor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl<'de> Deserialize<'de> for BadgeColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Picks a color for a numeric value, e.g. coverage or build time.
+///
+/// Parses from `value:color` stops. Steps are separated by `,`, gradient
+/// stops by `..`:
+///
+/// ```
+/// use badgeland::ColorScale;
+///
+/// let steps: ColorScale = "0:red,50:yellow,80:green".parse().unwrap();
+/// let gradient: ColorScale = "0:red..50:yellow..100:green".parse().unwrap();
+///
+/// assert_eq!(steps.color(65.), Some("yellow".parse().unwrap()));
+/// assert_eq!(gradient.color(100.), Some("green".parse().unwrap()));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum ColorScale {
+    /// Ordered thresholds. A value gets the color of the highest threshold it
+    /// reaches, no color below the first one.
+    Steps(Vec<(f32, Color)>),
+    /// Linear interpolation between two or three stops, clamped at the ends.
+    Gradient(Vec<(f32, Color)>),
+}
+
+impl ColorScale {
+    pub fn steps(steps: Vec<(f32, Color)>) -> Result<Self, ColorScaleError> {
+        if steps.is_empty() || !is_ordered(&steps) {
+            return Err(ColorScaleError);
+        }
+        Ok(ColorScale::Steps(steps))
+    }
+
+    /// Stops must be ordered and use sRGB colors (named, hex or `rgb()`).
+    pub fn gradient(stops: Vec<(f32, Color)>) -> Result<Self, ColorScaleError> {
+        let valid = (2..=3).contains(&stops.len())
+            && is_ordered(&stops)
+            && stops.iter().all(|(_, c)| c.rgba().is_some());
+        if !valid {
+            return Err(ColorScaleError);
+        }
+        Ok(ColorScale::Gradient(stops))
+    }
+
+    /// Color for `value`. `None` for non-finite values and values below the
+    /// first step.
+    pub fn color(&self, value: f32) -> Option<Color> {
+        if !value.is_finite() {
+            return None;
+        }
+        match self {
+            ColorScale::Steps(steps) => steps
+                .iter()
+                .rev()
+                .find(|(min, _)| value >= *min)
+                .map(|(_, c)| c.clone()),
+            ColorScale::Gradient(stops) => {
+                let (first, last) = (&stops[0], &stops[stops.len() - 1]);
+                if value <= first.0 {
+                    return Some(first.1.clone());
+                }
+                let i = stops.iter().position(|(v, _)| value < *v);
+                let (lo, hi) = match i {
+                    Some(i) => (&stops[i - 1], &stops[i]),
+                    None => return Some(last.1.clone()),
+                };
+                let t = (value - lo.0) / (hi.0 - lo.0);
+                Some(mix(&lo.1, &hi.1, t))
+            }
+        }
+    }
+}
+
+#[inline]
+fn is_ordered(stops: &[(f32, Color)]) -> bool {
+    stops.iter().all(|(v, _)| v.is_finite()) && stops.windows(2).all(|w| w[0].0 < w[1].0)
+}
+
+fn mix(a: &Color, b: &Color, t: f32) -> Color {
+    // Both were checked when the gradient was built
+    let (a, b) = (a.rgba().unwrap(), b.rgba().unwrap());
+    let lerp = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t) / 255.0;
+    let c = RgbaLegacy::from_floats(
+        lerp(a.red, b.red),
+        lerp(a.green, b.green),
+        lerp(a.blue, b.blue),
+        a.alpha + (b.alpha - a.alpha) * t,
+    );
+    let mut w = String::new();
+    let _ = c.to_css(&mut w);
+    Color(w.into())
+}
+
+// Split on `sep` outside of parentheses, so `rgb(1, 2, 3)` stays whole.
+fn split_top_level<'s>(s: &'s str, sep: &str) -> Vec<&'s str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            // Still inside the last separator
+            _ if i < start => {}
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && s[i..].starts_with(sep) => {
+                parts.push(&s[start..i]);
+                start = i + sep.len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+impl FromStr for ColorScale {
+    type Err = ColorScaleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let gradient = split_top_level(s, "..");
+        let is_gradient = gradient.len() > 1;
+        let items = if is_gradient {
+            gradient
+        } else {
+            split_top_level(s, ",")
+        };
+
+        let stops = items
+            .into_iter()
+            .map(|item| {
+                let (value, color) = item.split_once(':').ok_or(ColorScaleError)?;
+                let value = value.trim().parse().map_err(|_| ColorScaleError)?;
+                let color = color.trim().parse().map_err(|_| ColorScaleError)?;
+                Ok((value, color))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if is_gradient {
+            ColorScale::gradient(stops)
+        } else {
+            ColorScale::steps(stops)
+        }
+    }
+}
+
+impl Display for ColorScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (stops, sep) = match self {
+            ColorScale::Steps(s) => (s, ","),
+            ColorScale::Gradient(s) => (s, ".."),
+        };
+        for (i, (value, color)) in stops.iter().enumerate() {
+            if i > 0 {
+                f.write_str(sep)?;
+            }
+            write!(f, "{}:{}", value, color)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl Serialize for ColorScale {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde_de")]
+impl<'de> Deserialize<'de> for ColorScale {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Color;
+    use super::{BadgeColor, Color, ColorScale};
     use std::str::FromStr;
 
     #[test]
@@ -151,4 +398,68 @@ mod test {
             assert_eq!(cx.unwrap_err().to_string(), "Invalid Color".to_string());
         }
     }
+
+    fn c(s: &str) -> Color {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn color_scale_steps() {
+        let scale: ColorScale = "0:red,50:yellow,80:green".parse().unwrap();
+        assert_eq!(scale.color(-1.), None);
+        assert_eq!(scale.color(0.), Some(c("red")));
+        assert_eq!(scale.color(79.9), Some(c("yellow")));
+        assert_eq!(scale.color(80.), Some(c("green")));
+        assert_eq!(scale.color(f32::NAN), None);
+    }
+
+    #[test]
+    fn color_scale_gradient() {
+        let scale: ColorScale = "0:#000..100:#fff".parse().unwrap();
+        assert_eq!(scale.color(-5.), Some(c("black")));
+        assert_eq!(scale.color(50.), Some(c("rgb(128, 128, 128)")));
+        assert_eq!(scale.color(500.), Some(c("white")));
+
+        let scale: ColorScale = "0:red..50:rgb(255, 255, 0)..100:lime".parse().unwrap();
+        assert_eq!(scale.color(25.), Some(c("rgb(255, 128, 0)")));
+        assert_eq!(scale.color(75.), Some(c("rgb(128, 255, 0)")));
+    }
+
+    #[test]
+    fn color_scale_round_trips() {
+        for s in ["0:red,50:yellow", "0:red..10.5:rgba(0, 0, 255, 0.5)"] {
+            let scale: ColorScale = s.parse().unwrap();
+            assert_eq!(scale.to_string().parse::<ColorScale>().unwrap(), scale);
+        }
+    }
+
+    #[test]
+    fn color_scale_invalid() {
+        for s in [
+            "",
+            "red",
+            "50:red,10:green",
+            "a:red",
+            "0:nope",
+            "0:red..1:green..2:blue..3:white",
+            "0:red..1:lab(50% 40 59.5)",
+            "0:réd",
+            "0:é..1:green",
+            "é,0:red",
+        ] {
+            let scale = s.parse::<ColorScale>();
+            assert!(scale.is_err(), "input = {}, received = {:?}", s, scale);
+        }
+    }
+
+    #[test]
+    fn badge_color_from_str() {
+        assert_eq!("Brand".parse::<BadgeColor>().unwrap(), BadgeColor::Brand);
+        assert_eq!(
+            "red".parse::<BadgeColor>().unwrap(),
+            BadgeColor::Css("red".parse().unwrap())
+        );
+        assert!("brands".parse::<BadgeColor>().is_err());
+        assert_eq!(BadgeColor::Brand.to_string(), "brand");
+    }
 }
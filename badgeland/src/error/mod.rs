@@ -1,13 +1,65 @@
+use std::{
+    num::{ParseFloatError, ParseIntError},
+    str::ParseBoolError,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 #[error("Invalid Color")]
 pub struct ColorError;
 
-#[cfg(feature = "static_icons")]
 #[derive(Error, Debug)]
-#[error("Invalid Icon")]
-pub struct IconError;
+#[error("Invalid Color Scale")]
+pub struct ColorScaleError;
+
+#[derive(Error, Debug, Default)]
+#[error("Invalid Icon{}", did_you_mean(.suggestions))]
+pub struct IconError {
+    suggestions: Vec<String>,
+}
+
+impl IconError {
+    pub(crate) fn new(suggestions: Vec<&str>) -> Self {
+        let suggestions = suggestions.into_iter().map(String::from).collect();
+        IconError { suggestions }
+    }
+
+    /// Names of similar icons, best match first.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        s => format!(". Did you mean {}?", s.join(", ")),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SvgError {
+    #[error("Invalid Svg: {0}")]
+    Parse(#[from] roxmltree::Error),
+    #[error("Invalid Svg: root element is not svg")]
+    NotSvg,
+    #[error("Invalid Svg: missing or invalid viewBox")]
+    ViewBox,
+    #[error("Invalid Svg: nothing to draw")]
+    Empty,
+    #[error("Invalid icon name")]
+    Name,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum ImageError {
+    #[error("Invalid Image: expected png or jpeg")]
+    Format,
+    #[error("Invalid Image: expected a base64 data uri")]
+    DataUri,
+}
 
 #[derive(Error, Debug)]
 #[error("Invalid Size")]
@@ -16,3 +68,58 @@ pub struct SizeError;
 #[derive(Error, Debug)]
 #[error("Invalid Style")]
 pub struct StyleError;
+
+#[derive(Error, Debug)]
+#[error("Invalid Chart")]
+pub struct ChartError;
+
+#[derive(Error, Debug)]
+#[error("Invalid Id Prefix, expected a letter followed by letters, digits, `-` or `_`")]
+pub struct IdPrefixError;
+
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("Invalid Font")]
+    Invalid,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum SpecError {
+    #[error("Invalid query: {0}")]
+    Query(String),
+    #[error(transparent)]
+    Color(#[from] ColorError),
+    #[error(transparent)]
+    ColorScale(#[from] ColorScaleError),
+    #[error(transparent)]
+    Size(#[from] SizeError),
+    #[error(transparent)]
+    Style(#[from] StyleError),
+    #[error(transparent)]
+    Chart(#[from] ChartError),
+    #[error(transparent)]
+    IdPrefix(#[from] IdPrefixError),
+    #[error("Invalid Progress: {0}")]
+    Progress(String),
+    #[error("Invalid Segment: {0}")]
+    Segment(String),
+    #[error("Invalid Data")]
+    Data(#[from] ParseFloatError),
+    #[error("Invalid Max Width")]
+    MaxWidth(#[from] ParseIntError),
+    #[error("Invalid Flag, expected true or false")]
+    Flag(#[from] ParseBoolError),
+}
+
+#[cfg(feature = "raster")]
+#[derive(Error, Debug)]
+pub enum RasterError {
+    #[error("Invalid scale: {0}")]
+    Scale(f32),
+    #[error("Invalid svg: {0}")]
+    Svg(String),
+    #[error("Failed to encode image: {0}")]
+    Encode(String),
+}
@@ -1,12 +1,25 @@
+use super::error::{ImageError, SvgError};
+use sailfish::runtime::escape::escape_to_string;
 #[cfg(feature = "static_icons")]
 use std::convert::TryFrom;
+use std::{borrow::Cow, collections::HashMap, fs, path::Path};
+
+use super::{error::IconError, Color};
 
 #[cfg(feature = "static_icons")]
-use super::error::IconError;
+use once_cell::sync::Lazy;
 
 #[cfg(feature = "static_icons")]
 include!(concat!(env!("OUT_DIR"), "/icons_map.rs"));
 
+// Built-in symbols are stored deflated and inflated once, on first lookup
+#[cfg(feature = "static_icons")]
+static SYMBOL_DATA: Lazy<String> = Lazy::new(|| {
+    let data =
+        miniz_oxide::inflate::decompress_to_vec(SYMBOLS_DEFLATED).expect("Error inflating icons");
+    String::from_utf8(data).expect("Error inflating icons")
+});
+
 #[cfg(feature = "static_icons")]
 pub fn icon_exists(icon_name: &str) -> bool {
     SYMBOLS.contains_key(icon_name)
@@ -14,25 +27,329 @@ pub fn icon_exists(icon_name: &str) -> bool {
 
 #[cfg(feature = "static_icons")]
 pub fn icon_keys() -> Vec<&'static str> {
-    SYMBOLS.keys().map(|&k| k).collect()
+    SYMBOLS.keys().copied().collect()
+}
+
+/// Size in bytes of the built-in icons as embedded in the binary, and once
+/// inflated on first use.
+#[cfg(feature = "static_icons")]
+pub fn icon_data_size() -> (usize, usize) {
+    (SYMBOLS_DEFLATED.len(), SYMBOL_DATA.len())
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Icon<'a> {
     name: &'a str,
-    symbol: &'a str,
+    source: IconSource<'a>,
+    meta: Option<IconMeta>,
+}
+
+/// Metadata of a built-in icon.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IconMeta {
+    title: &'static str,
+    color: Option<&'static str>,
+    pack: &'static str,
+    license: &'static str,
+}
+
+impl IconMeta {
+    /// Display name, e.g. `GitHub`. The icon id for packs without titles.
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    /// Official brand color, for packs that publish one.
+    pub fn color(&self) -> Option<Color> {
+        self.color.and_then(|hex| format!("#{}", hex).parse().ok())
+    }
+
+    /// The icon pack, e.g. `fa-solid` or `simple-icons`.
+    pub fn pack(&self) -> &'static str {
+        self.pack
+    }
+
+    /// License of the icon pack, as an SPDX id.
+    pub fn license(&self) -> &'static str {
+        self.license
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum IconSource<'a> {
+    /// An svg `<symbol>`
+    Symbol(Cow<'a, str>),
+    /// A raster image as a `data:` uri
+    Image(Cow<'a, str>),
 }
 
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+// Elements and attributes kept by `Icon::from_svg`, everything else is dropped
+const SVG_ELEMENTS: [&str; 8] = [
+    "circle", "ellipse", "g", "line", "path", "polygon", "polyline", "rect",
+];
+
+const SVG_ATTRIBUTES: [&str; 30] = [
+    "clip-rule",
+    "cx",
+    "cy",
+    "d",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "height",
+    "opacity",
+    "points",
+    "r",
+    "rx",
+    "ry",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "transform",
+    "vector-effect",
+    "width",
+    "x",
+    "x1",
+    "x2",
+    "y",
+    "y1",
+    "y2",
+];
+
 impl<'a> Icon<'a> {
     pub fn new(name: &'a str, symbol: &'a str) -> Icon<'a> {
-        Icon { name, symbol }
+        Icon {
+            name,
+            source: IconSource::Symbol(Cow::Borrowed(symbol)),
+            meta: None,
+        }
+    }
+
+    /// Build an icon from a standalone svg document.
+    ///
+    /// Only basic shapes, paths and groups with presentation attributes are
+    /// kept, so scripts, `foreignObject`, event handlers and external
+    /// references never reach the badge. Shapes without a `fill` take the
+    /// badge's icon color.
+    pub fn from_svg(name: &'a str, svg: &str) -> Result<Icon<'a>, SvgError> {
+        let doc = roxmltree::Document::parse(svg)?;
+        let root = doc.root_element();
+        if !is_svg_element(root) || root.tag_name().name() != "svg" {
+            return Err(SvgError::NotSvg);
+        }
+        Ok(Icon {
+            name,
+            source: IconSource::Symbol(Cow::Owned(sanitized_symbol(name, root)?)),
+            meta: None,
+        })
+    }
+
+    /// Build an icon from png or jpeg bytes. The image is embedded in the
+    /// badge as a `data:` uri and keeps its own colors.
+    pub fn from_image(name: &'a str, bytes: &[u8]) -> Result<Icon<'a>, ImageError> {
+        let (mime, _) = IMAGE_FORMATS
+            .iter()
+            .find(|(_, magic)| bytes.starts_with(magic))
+            .ok_or(ImageError::Format)?;
+        let mut uri = format!("data:{};base64,", mime);
+        base64_encode(bytes, &mut uri);
+        Ok(Icon {
+            name,
+            source: IconSource::Image(Cow::Owned(uri)),
+            meta: None,
+        })
+    }
+
+    /// Build an icon from a base64 `data:` uri of a png or jpeg image.
+    pub fn from_data_uri(name: &'a str, uri: &'a str) -> Result<Icon<'a>, ImageError> {
+        let (mime, data) = uri
+            .strip_prefix("data:")
+            .and_then(|u| u.split_once(";base64,"))
+            .ok_or(ImageError::DataUri)?;
+        let mime = match mime.to_ascii_lowercase().as_str() {
+            "image/png" => "image/png",
+            "image/jpeg" | "image/jpg" => "image/jpeg",
+            _ => return Err(ImageError::Format),
+        };
+        let is_base64 = data.len() % 4 == 0
+            && data.trim_end_matches('=').len() + 2 >= data.len()
+            && data
+                .trim_end_matches('=')
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/');
+        if !is_base64 {
+            return Err(ImageError::DataUri);
+        }
+
+        // Check the decoded magic bytes match the declared type
+        let (_, magic) = IMAGE_FORMATS.iter().find(|(m, _)| *m == mime).unwrap();
+        let mut encoded = String::new();
+        base64_encode(magic, &mut encoded);
+        let whole = magic.len() / 3 * 4;
+        if !data.starts_with(&encoded[..whole]) {
+            return Err(ImageError::Format);
+        }
+        Ok(Icon {
+            name,
+            source: IconSource::Image(Cow::Borrowed(uri)),
+            meta: None,
+        })
     }
+
     pub fn name(&self) -> &'a str {
         self.name
     }
-    pub fn symbol(&self) -> &'a str {
-        self.symbol
+
+    /// The svg `<symbol>`, empty for raster images.
+    pub fn symbol(&self) -> &str {
+        match &self.source {
+            IconSource::Symbol(s) => s,
+            IconSource::Image(_) => "",
+        }
+    }
+
+    /// The `data:` uri of a raster image icon.
+    pub fn image(&self) -> Option<&str> {
+        match &self.source {
+            IconSource::Image(uri) => Some(uri),
+            IconSource::Symbol(_) => None,
+        }
+    }
+
+    /// Title, brand color, pack and license of a built-in icon.
+    pub fn meta(&self) -> Option<&IconMeta> {
+        self.meta.as_ref()
+    }
+}
+
+const IMAGE_FORMATS: [(&str, &[u8]); 2] = [
+    ("image/png", b"\x89PNG\r\n\x1a\n"),
+    ("image/jpeg", b"\xff\xd8\xff"),
+];
+
+fn base64_encode(bytes: &[u8], out: &mut String) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    out.reserve(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+/// `<symbol>` with the drawable content of `node`, an `<svg>` or `<symbol>`.
+fn sanitized_symbol(name: &str, node: roxmltree::Node) -> Result<String, SvgError> {
+    let valid_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    if name.is_empty() || !name.chars().all(valid_name) {
+        return Err(SvgError::Name);
+    }
+
+    let mut body = String::new();
+    for child in node.children() {
+        write_element(child, &mut body);
+    }
+    if body.is_empty() {
+        return Err(SvgError::Empty);
     }
+
+    let [x, y, w, h] = view_box(node).ok_or(SvgError::ViewBox)?;
+    Ok(format!(
+        r#"<symbol id="{}" viewBox="{} {} {} {}">{}</symbol>"#,
+        name, x, y, w, h, body
+    ))
+}
+
+fn is_svg_element(node: roxmltree::Node) -> bool {
+    node.is_element() && matches!(node.tag_name().namespace(), None | Some(SVG_NS))
+}
+
+/// `viewBox` of the svg, falling back to its `width` and `height`.
+fn view_box(svg: roxmltree::Node) -> Option<[f32; 4]> {
+    let values: Vec<f32> = match svg.attribute("viewBox") {
+        Some(v) => v
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?,
+        None => {
+            let len = |a| svg.attribute(a)?.trim_end_matches("px").parse().ok();
+            vec![0., 0., len("width")?, len("height")?]
+        }
+    };
+    match values[..] {
+        [x, y, w, h] if values.iter().all(|v| v.is_finite()) && w > 0. && h > 0. => {
+            Some([x, y, w, h])
+        }
+        _ => None,
+    }
+}
+
+// `url(...)` would point at paint servers that are not kept. Css functions are
+// case insensitive and may have whitespace before the parenthesis.
+fn has_url(value: &str) -> bool {
+    let value: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    value.contains("url(")
+}
+
+fn write_element(node: roxmltree::Node, out: &mut String) {
+    let name = node.tag_name().name();
+    if !is_svg_element(node) || !SVG_ELEMENTS.contains(&name) {
+        return;
+    }
+    out.push('<');
+    out.push_str(name);
+    for attr in node.attributes() {
+        let value = attr.value();
+        if attr.namespace().is_some() || !SVG_ATTRIBUTES.contains(&attr.name()) || has_url(value) {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(attr.name());
+        out.push_str("=\"");
+        escape_to_string(value, out);
+        out.push('"');
+    }
+    out.push('>');
+    for child in node.children() {
+        write_element(child, out);
+    }
+    out.push_str("</");
+    out.push_str(name);
+    out.push('>');
+}
+
+/// Built-in icon by namespaced (`fa:git`, `si:git`) or unprefixed name.
+#[cfg(feature = "static_icons")]
+fn static_icon(name: &str) -> Option<Icon<'_>> {
+    let &(start, len, meta) = SYMBOLS.get(name)?;
+    let symbol = &SYMBOL_DATA[start as usize..(start + len) as usize];
+    // Built-in symbols are stored without an id, as one is shared by several names
+    let symbol = symbol.strip_prefix("<symbol")?;
+    Some(Icon {
+        name,
+        source: IconSource::Symbol(Cow::Owned(format!(r#"<symbol id="{}"{}"#, name, symbol))),
+        meta: Some(meta),
+    })
 }
 
 #[cfg(feature = "static_icons")]
@@ -40,27 +357,187 @@ impl<'a> TryFrom<&'a str> for Icon<'a> {
     type Error = IconError;
 
     fn try_from(name: &'a str) -> Result<Self, Self::Error> {
-        SYMBOLS
-            .get(name)
-            .map(|&symbol| Icon { name, symbol })
-            .ok_or(Self::Error {})
+        static_icon(name).ok_or_else(|| IconError::new(search_icons(name, SUGGESTIONS)))
+    }
+}
+
+// Number of similar names suggested for an unknown icon
+const SUGGESTIONS: usize = 3;
+
+/// Built-in icon names matching `query`, best first: exact, prefix and
+/// substring matches, then names a few typos away. Queries with a pack
+/// prefix such as `fa:` search namespaced names, others unprefixed names.
+#[cfg(feature = "static_icons")]
+pub fn search_icons(query: &str, limit: usize) -> Vec<&'static str> {
+    rank_names(SYMBOLS.keys().copied(), query, limit)
+}
+
+fn rank_names<'n>(names: impl Iterator<Item = &'n str>, query: &str, limit: usize) -> Vec<&'n str> {
+    let query = query.trim().to_lowercase();
+    let namespaced = query.contains(':');
+    let max_distance = (query.chars().count() / 3).max(1);
+
+    let mut ranked: Vec<(usize, usize, &str)> = names
+        .filter(|name| name.contains(':') == namespaced)
+        .filter_map(|name| {
+            let lower = name.to_lowercase();
+            let score = if lower == query {
+                0
+            } else if lower.starts_with(&query) {
+                1
+            } else if lower.contains(&query) {
+                2
+            } else {
+                match edit_distance(&query, &lower) {
+                    d if d <= max_distance => 2 + d,
+                    _ => return None,
+                }
+            };
+            Some((score, name.len(), name))
+        })
+        .collect();
+    ranked.sort_unstable();
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, name)| name)
+        .collect()
+}
+
+/// Edits, counting a swap of adjacent chars as one, to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // Rows for the two previous chars of `a` and the current one
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+/// Icons by name: symbols added at runtime from svg sprites, then the
+/// built-in icons of the enabled icon pack features.
+///
+/// Sprites use the same format as the built-in icon sets, an `<svg>` with
+/// one `<symbol id="...">` per icon. Symbols are sanitized like
+/// `Icon::from_svg`, and replace built-in icons with the same name.
+///
+/// ```no_run
+/// use badgeland::{Badge, IconRegistry};
+///
+/// let mut icons = IconRegistry::new();
+/// icons.add_sprite_file("icons/company.svg")?;
+///
+/// let mut badge = Badge::new();
+/// badge.subject("deploy").icon(icons.get("rocket")?);
+/// let svg = badge.text("ok").to_string();
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct IconRegistry {
+    symbols: HashMap<String, String>,
+}
+
+impl IconRegistry {
+    /// Registry with only the built-in icons, if any.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every symbol of an svg sprite. Nothing is added if any symbol is invalid.
+    pub fn add_sprite(&mut self, sprite: &str) -> Result<&mut Self, SvgError> {
+        let doc = roxmltree::Document::parse(sprite)?;
+        let root = doc.root_element();
+        if !is_svg_element(root) || root.tag_name().name() != "svg" {
+            return Err(SvgError::NotSvg);
+        }
+        let symbols = root
+            .descendants()
+            .filter(|n| is_svg_element(*n) && n.tag_name().name() == "symbol")
+            .map(|n| {
+                let name = n.attribute("id").ok_or(SvgError::Name)?;
+                Ok((name.to_string(), sanitized_symbol(name, n)?))
+            })
+            .collect::<Result<Vec<_>, SvgError>>()?;
+        self.symbols.extend(symbols);
+        Ok(self)
+    }
+
+    pub fn add_sprite_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, SvgError> {
+        let sprite = fs::read_to_string(path)?;
+        self.add_sprite(&sprite)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Icon<'_>, IconError> {
+        if let Some((name, symbol)) = self.symbols.get_key_value(name) {
+            return Ok(Icon {
+                name,
+                source: IconSource::Symbol(Cow::Borrowed(symbol)),
+                meta: None,
+            });
+        }
+        #[cfg(feature = "static_icons")]
+        if let Some(icon) = SYMBOLS.get_key(name).and_then(|name| static_icon(name)) {
+            return Ok(icon);
+        }
+        Err(IconError::new(self.search(name, SUGGESTIONS)))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        #[cfg(feature = "static_icons")]
+        if SYMBOLS.contains_key(name) {
+            return true;
+        }
+        self.symbols.contains_key(name)
+    }
+
+    /// Icon names matching `query`, ranked like `search_icons`.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&str> {
+        rank_names(self.names().into_iter(), query, limit)
+    }
+
+    /// Names of all icons, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.symbols.keys().map(String::as_str).collect();
+        #[cfg(feature = "static_icons")]
+        names.extend(SYMBOLS.keys().copied());
+        names.sort_unstable();
+        names.dedup();
+        names
     }
 }
 
 #[cfg(test)]
 mod tests {
-    #[cfg(feature = "static_icons")]
-    use super::{icon_keys, Icon, SYMBOLS};
-    use std::convert::TryFrom;
+    #[cfg(feature = "icons-simple")]
+    use super::search_icons;
+    use super::{base64_encode, edit_distance, rank_names, Icon, IconRegistry};
+    // Packs vendored in build_scripts/icons, brands.svg has to be downloaded
+    #[cfg(any(feature = "icons-fa-solid", feature = "icons-simple"))]
+    use super::{icon_data_size, icon_keys, SYMBOLS};
+    use crate::{ImageError, SvgError};
 
     #[test]
+    #[cfg(feature = "icons-simple")]
     fn get_icon_symbol_pass() {
         let icon = Icon::try_from("bluetooth");
         assert!(icon.is_ok());
-        assert!(icon.unwrap().symbol.len() > 0);
+        assert!(!icon.unwrap().symbol().is_empty());
     }
 
     #[test]
+    #[cfg(feature = "static_icons")]
     fn get_icon_symbol_fail() {
         let icon = Icon::try_from("someicon");
         assert!(icon.is_err());
@@ -68,8 +545,300 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "icons-fa-solid")]
+    fn get_namespaced_icon() {
+        let icon = Icon::try_from("fa:code").unwrap();
+        assert_eq!(icon.name(), "fa:code");
+        assert!(icon
+            .symbol()
+            .starts_with(r#"<symbol id="fa:code" viewBox="#));
+
+        // Unprefixed names fall back to the first pack with the id
+        let plain = Icon::try_from("code").unwrap();
+        assert!(plain.symbol().starts_with(r#"<symbol id="code" viewBox="#));
+        assert_eq!(plain.symbol()[16..], icon.symbol()[19..]);
+
+        assert!(Icon::try_from("si:code").is_err());
+        assert!(Icon::try_from("fa:").is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "icons-fa-solid", feature = "icons-simple"))]
     fn get_icon_keys() {
-        assert!(icon_keys().len() > 0);
+        assert!(!icon_keys().is_empty());
         assert!(SYMBOLS.contains_key(icon_keys()[0]))
     }
+
+    #[test]
+    #[cfg(any(feature = "icons-fa-solid", feature = "icons-simple"))]
+    fn built_in_icons_are_deflated() {
+        let (deflated, inflated) = icon_data_size();
+        assert!(deflated * 2 < inflated, "{} / {}", deflated, inflated);
+        for name in icon_keys() {
+            let icon = Icon::try_from(name).unwrap();
+            assert!(icon.symbol().ends_with("</symbol>"), "{}", name);
+            roxmltree::Document::parse(icon.symbol()).unwrap();
+        }
+    }
+
+    #[test]
+    fn from_svg_keeps_shapes() {
+        let svg = r#"<?xml version="1.0"?>
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0,0 24 24" width="24">
+              <title>logo</title>
+              <g transform="translate(1 1)"><path d="M0 0h10v10z" fill-rule="evenodd"/></g>
+              <circle cx="12" cy="12" r="4" fill="red"/>
+            </svg>"#;
+        let icon = Icon::from_svg("logo", svg).unwrap();
+        assert_eq!(icon.name(), "logo");
+        assert!(icon.meta().is_none());
+        assert_eq!(
+            icon.symbol(),
+            r#"<symbol id="logo" viewBox="0 0 24 24"><g transform="translate(1 1)"><path d="M0 0h10v10z" fill-rule="evenodd"></path></g><circle cx="12" cy="12" r="4" fill="red"></circle></symbol>"#
+        );
+    }
+
+    #[test]
+    fn from_svg_strips_unsafe_content() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"
+                xmlns:xlink="http://www.w3.org/1999/xlink" width="16px" height="16">
+              <script>alert(1)</script>
+              <foreignObject><div xmlns="http://www.w3.org/1999/xhtml">x</div></foreignObject>
+              <path d="M0 0" onclick="alert(1)" style="fill:red" fill="url(https://x.y/z)"/>
+              <path d="M1 1" fill="URL(https://x.y/z)" stroke=" Url (#a)"/>
+              <g onload="alert(1)"><script>alert(2)</script><rect width="4" height="&quot;4"/></g>
+              <use xlink:href="https://x.y/icon.svg#a"/>
+            </svg>"#;
+        let symbol = Icon::from_svg("safe", svg).unwrap().symbol().to_string();
+        assert_eq!(
+            symbol,
+            r#"<symbol id="safe" viewBox="0 0 16 16"><path d="M0 0"></path><path d="M1 1"></path><g><rect width="4" height="&quot;4"></rect></g></symbol>"#
+        );
+    }
+
+    #[test]
+    fn from_svg_rejects_invalid_input() {
+        let path = r#"<path d="M0 0h1v1z"/>"#;
+        let svg = |attrs: &str, body: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" {}>{}</svg>"#,
+                attrs, body
+            )
+        };
+        let err = |name, svg: &str| Icon::from_svg(name, svg).unwrap_err();
+
+        assert!(matches!(err("a", "<svg"), SvgError::Parse(_)));
+        assert!(matches!(err("a", "<html><path/></html>"), SvgError::NotSvg));
+        assert!(matches!(err("a", &svg("", path)), SvgError::ViewBox));
+        assert!(matches!(
+            err("a", &svg(r#"viewBox="0 0 0 1""#, path)),
+            SvgError::ViewBox
+        ));
+        assert!(matches!(
+            err("a", &svg(r#"viewBox="0 0 1""#, path)),
+            SvgError::ViewBox
+        ));
+        assert!(matches!(
+            err("a", &svg(r#"viewBox="0 0 1 1""#, "<text>a</text>")),
+            SvgError::Empty
+        ));
+        for name in ["", "a b", "a\"", "<x>"] {
+            assert!(matches!(
+                err(name, &svg(r#"viewBox="0 0 1 1""#, path)),
+                SvgError::Name
+            ));
+        }
+
+        let doctype =
+            r#"<!DOCTYPE svg [<!ENTITY x "y">]><svg viewBox="0 0 1 1"><path d="&x;"/></svg>"#;
+        assert!(Icon::from_svg("a", doctype).is_err());
+    }
+
+    #[test]
+    fn encode_base64() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xff\xfe\xfd", "//79"),
+        ] {
+            let mut out = String::new();
+            base64_encode(bytes, &mut out);
+            assert_eq!(out, encoded);
+        }
+    }
+
+    #[test]
+    fn from_image_embeds_data_uri() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let icon = Icon::from_image("logo", png).unwrap();
+        let uri = icon.image().unwrap();
+        assert_eq!(uri, "data:image/png;base64,iVBORw0KGgoAAAANSUhEUg==");
+        assert_eq!(icon.symbol(), "");
+        assert_eq!(Icon::from_data_uri("logo", uri).unwrap(), icon);
+
+        let jpeg = Icon::from_image("logo", b"\xff\xd8\xff\xe0").unwrap();
+        assert!(jpeg
+            .image()
+            .unwrap()
+            .starts_with("data:image/jpeg;base64,/9j/"));
+
+        assert!(matches!(
+            Icon::from_image("a", b"GIF89a"),
+            Err(ImageError::Format)
+        ));
+    }
+
+    #[test]
+    fn from_data_uri_rejects_invalid_input() {
+        let err = |uri| Icon::from_data_uri("a", uri).unwrap_err();
+        assert!(matches!(err("iVBORw0KGgo="), ImageError::DataUri));
+        assert!(matches!(
+            err("data:image/png,iVBORw0KGgo="),
+            ImageError::DataUri
+        ));
+        assert!(matches!(
+            err("data:image/png;base64,iVBORw0KGgo"),
+            ImageError::DataUri
+        ));
+        assert!(matches!(
+            err("data:image/png;base64,iVBO\"/><x"),
+            ImageError::DataUri
+        ));
+        assert!(matches!(
+            err("data:image/png;base64,iV=BORw0KGg="),
+            ImageError::DataUri
+        ));
+        assert!(matches!(
+            err("data:image/svg+xml;base64,PHN2Zz4="),
+            ImageError::Format
+        ));
+        // Declared png, but the bytes are a jpeg
+        assert!(matches!(
+            err("data:image/png;base64,/9j/4AAA"),
+            ImageError::Format
+        ));
+        assert!(Icon::from_data_uri("a", "data:image/JPG;base64,/9j/4AAA").is_ok());
+    }
+
+    #[test]
+    fn registry_loads_sprites() {
+        let sprite = r#"<svg xmlns="http://www.w3.org/2000/svg" style="display: none;">
+              <defs><symbol id="dot" viewBox="0 0 2 2"><circle r="1"/></symbol></defs>
+              <symbol id="bar" viewBox="0 0 4 1"><rect width="4" height="1"/></symbol>
+            </svg>"#;
+        let mut icons = IconRegistry::new();
+        assert!(icons.get("dot").is_err());
+        icons.add_sprite(sprite).unwrap();
+
+        let dot = icons.get("dot").unwrap();
+        assert_eq!(dot.name(), "dot");
+        assert_eq!(
+            dot.symbol(),
+            r#"<symbol id="dot" viewBox="0 0 2 2"><circle r="1"></circle></symbol>"#
+        );
+        assert!(icons.contains("bar"));
+        assert!(icons.names().contains(&"bar"));
+
+        // An invalid symbol rejects the whole sprite
+        let bad = r#"<svg><symbol id="ok" viewBox="0 0 1 1"><path d="M0 0"/></symbol>
+            <symbol id="bad"><path d="M0 0"/></symbol></svg>"#;
+        assert!(matches!(icons.add_sprite(bad), Err(SvgError::ViewBox)));
+        assert!(!icons.contains("ok"));
+        assert!(matches!(icons.add_sprite("<g/>"), Err(SvgError::NotSvg)));
+        assert!(matches!(
+            icons.add_sprite_file("not/a/file.svg"),
+            Err(SvgError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn registry_accepts_bundled_sprites() {
+        let mut icons = IconRegistry::new();
+        icons
+            .add_sprite(include_str!("../build_scripts/icons/solid.svg"))
+            .unwrap();
+        assert_eq!(icons.symbols.len(), 1002);
+        assert!(icons.contains("ad"));
+    }
+
+    #[test]
+    #[cfg(feature = "icons-simple")]
+    fn registry_merges_static_icons() {
+        let mut icons = IconRegistry::new();
+        assert_eq!(icons.get("git").unwrap(), Icon::try_from("git").unwrap());
+        assert_eq!(icons.names(), {
+            let mut keys = icon_keys();
+            keys.sort_unstable();
+            keys
+        });
+
+        // Runtime icons replace built-in ones
+        let sprite = r#"<svg><symbol id="git" viewBox="0 0 1 1"><path d="M0 0"/></symbol></svg>"#;
+        icons.add_sprite(sprite).unwrap();
+        assert_ne!(icons.get("git").unwrap(), Icon::try_from("git").unwrap());
+        assert_eq!(icons.names().len(), icon_keys().len());
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps_once() {
+        assert_eq!(edit_distance("github", "github"), 0);
+        assert_eq!(edit_distance("githb", "github"), 1);
+        assert_eq!(edit_distance("gti", "git"), 1);
+        assert_eq!(edit_distance("", "npm"), 3);
+    }
+
+    #[test]
+    fn rank_names_orders_matches() {
+        let names = ["git", "github", "gitlab", "digit", "gift", "fa:git", "npm"];
+        let rank = |query, limit| rank_names(names.iter().copied(), query, limit);
+        assert_eq!(
+            rank("git", 10),
+            ["git", "github", "gitlab", "digit", "gift"]
+        );
+        assert_eq!(rank("GIT", 2), ["git", "github"]);
+        assert_eq!(rank("gti", 10), ["git"]);
+        assert_eq!(rank("fa:gi", 10), ["fa:git"]);
+        assert!(rank("zzzzzz", 10).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "icons-simple")]
+    fn unknown_icon_suggests_names() {
+        assert_eq!(search_icons("githb", 3), ["github"]);
+        let err = Icon::try_from("githb").unwrap_err();
+        assert_eq!(err.suggestions(), ["github"]);
+        assert_eq!(err.to_string(), "Invalid Icon. Did you mean github?");
+
+        let mut icons = IconRegistry::new();
+        let sprite =
+            r#"<svg><symbol id="githab" viewBox="0 0 1 1"><path d="M0 0"/></symbol></svg>"#;
+        icons.add_sprite(sprite).unwrap();
+        assert_eq!(
+            icons.get("githb").unwrap_err().suggestions(),
+            ["githab", "github"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "icons-simple")]
+    fn simple_icons_have_brand_meta() {
+        let meta = *Icon::try_from("si:github").unwrap().meta().unwrap();
+        assert_eq!(meta.title(), "GitHub");
+        assert_eq!(meta.color(), Some("#181717".parse().unwrap()));
+        assert_eq!(meta.pack(), "simple-icons");
+        assert_eq!(meta.license(), "CC0-1.0");
+    }
+
+    #[test]
+    #[cfg(feature = "icons-fa-solid")]
+    fn fa_icons_have_pack_meta() {
+        let meta = *Icon::try_from("fa:code").unwrap().meta().unwrap();
+        assert_eq!(meta.title(), "code");
+        assert_eq!(meta.color(), None);
+        assert_eq!(meta.pack(), "fa-solid");
+        assert_eq!(meta.license(), "CC-BY-4.0");
+    }
 }
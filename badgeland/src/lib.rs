@@ -33,22 +33,76 @@ fn badge_with_data() {
 }
 ```
 This produce a svg badge: ![](http://badge.land/b/testing/12,34,23,56,45)
+```rust
+use badgeland::{Badge};
+
+fn badge_with_progress() {
+    let mut badge = Badge::new();
+    badge.subject("coverage");
+    println!("{}", badge.progress(87.5).to_string());
+}
+```
+
+# Inline svg
+
+Element ids are global in an html page. When inlining several badges, give
+each one its own id prefix with `Badge::id_prefix` or `Badge::hashed_ids`.
+
+# Custom icons
+
+`Icon::from_svg` turns a standalone svg into an icon. Only shapes, paths and
+groups are kept, so scripts and event handlers in the file are dropped.
+`Icon::from_image` and `Icon::from_data_uri` embed a png or jpeg logo instead.
+`IconRegistry` looks icons up by name from svg sprites loaded at runtime and
+the built-in icons, so it also works without the `static_icons` feature.
+Built-in icons have an `IconMeta` with their title, pack, license and, for
+Simple Icons, the brand color a `BadgeSpec` uses for `color=brand`.
+
+# Outlined text
+
+Viewers without the badge font substitute another one, so text may not fit.
+`Badge::outline_text` draws text as paths from the font's glyph outlines
+instead, which render the same everywhere.
+
+# Raster output
+
+Enable the `raster` feature to render badges to PNG or WebP using the bundled font.
+
+```rust,ignore
+use badgeland::Badge;
+
+fn badge_png() -> Vec<u8> {
+    let mut badge = Badge::new();
+    badge.subject("Subject");
+    badge.text("Text").render_png(2.0).unwrap()
+}
+```
 
 */
 
 mod badge;
 mod badge_data;
+mod badge_spec;
 mod color;
 mod error;
 mod icons;
 
-pub use badge::{Badge, Size, Style};
+pub use badge::{
+    Badge, BadgeLayout, Chart, FontSet, Point, Rect, Segment, SegmentLayout, Size, Style,
+};
+#[cfg(feature = "raster")]
+pub use badge::RasterFormat;
 pub use badge_data::BadgeData;
+pub use badge_spec::{BadgeContent, BadgeSpec, SegmentSpec};
 pub use color::*;
 pub use error::*;
-pub use icons::Icon;
+pub use icons::{Icon, IconMeta, IconRegistry};
 
 #[cfg(feature = "static_icons")]
-pub use icons::{icon_exists, icon_keys};
+pub use icons::{icon_exists, icon_keys, search_icons};
+// Only for the icon size bench
+#[cfg(feature = "static_icons")]
+#[doc(hidden)]
+pub use icons::icon_data_size;
 
 pub type InitialBadge<'a> = Badge<'a, badge::BadgeTypeInit>;
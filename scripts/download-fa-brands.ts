@@ -0,0 +1,18 @@
+import { join, dirname, fromFileUrl } from "https://deno.land/std@0.103.0/path/mod.ts";
+
+// Same Font Awesome release as build_scripts/icons/solid.svg
+const FA_VERSION = "5.15.2";
+
+const res = await fetch(
+  `https://raw.githubusercontent.com/FortAwesome/Font-Awesome/${FA_VERSION}/sprites/brands.svg`
+);
+if (!res.ok) {
+  throw new Error(`Failed to download brands sprite: ${res.status} ${res.statusText}`);
+}
+
+const __dirname = dirname(fromFileUrl(import.meta.url));
+
+Deno.writeFileSync(
+  join(__dirname, "../badgeland/build_scripts/icons/brands.svg"),
+  new Uint8Array(await res.arrayBuffer())
+);
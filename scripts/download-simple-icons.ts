@@ -3,18 +3,25 @@ import { join, dirname, fromFileUrl } from "https://deno.land/std@0.103.0/path/m
 import { cheerio } from "https://deno.land/x/cheerio@1.0.4/mod.ts";
 import icons from "https://cdn.skypack.dev/simple-icons/index.js?dts";
 
-type SimpleIcon = { slug: string; svg: string };
+type SimpleIcon = { title: string; slug: string; hex: string; svg: string };
 
 const svgSprite = cheerio('<svg xmlns="http://www.w3.org/2000/svg" style="display: none;"></svg>');
 
-for (const { slug, svg } of Object.values(icons) as SimpleIcon[]) {
+for (const { title, slug, hex, svg } of Object.values(icons) as SimpleIcon[]) {
   const $ = cheerio.load(svg);
 
   const vbox = $("svg").attr("viewBox")!;
 
   const path = cheerio.html($("svg > path"));
 
-  cheerio("<symbol></symbol>").attr("id", slug).attr("viewBox", vbox).append(path).appendTo(svgSprite);
+  // Read by build_scripts/build.rs into the icon metadata
+  cheerio("<symbol></symbol>")
+    .attr("id", slug)
+    .attr("data-title", title)
+    .attr("data-color", hex)
+    .attr("viewBox", vbox)
+    .append(path)
+    .appendTo(svgSprite);
 }
 
 const __dirname = dirname(fromFileUrl(import.meta.url));